@@ -0,0 +1,104 @@
+// Genereto search widget.
+// Searches the search-index.json written by genereto when `search_index: true` is set in config.yml.
+// Usage:
+//   <div id="genereto-search"></div>
+//   <script src="res/search.js" data-index="search-index.json" defer></script>
+// `data-index` is the path of the index relative to the page, `data-container` the id of the
+// element where the search box is added (defaults to "genereto-search").
+(function () {
+    "use strict";
+
+    const MAX_RESULTS = 10;
+    const SNIPPET_LENGTH = 160;
+    // Matches in the title count more than matches in the body.
+    const WEIGHTS = { title: 10, keywords: 5, description: 3, body: 1 };
+
+    const script = document.currentScript;
+    const indexUrl = new URL(script.dataset.index || "search-index.json", document.baseURI);
+    const container = document.getElementById(script.dataset.container || "genereto-search");
+    if (!container) {
+        return;
+    }
+
+    const input = document.createElement("input");
+    input.type = "search";
+    input.placeholder = "Search...";
+    input.setAttribute("aria-label", "Search");
+    input.className = "genereto-search-input";
+    const results = document.createElement("ul");
+    results.className = "genereto-search-results";
+    container.append(input, results);
+
+    let index = null;
+    function loadIndex() {
+        if (!index) {
+            index = fetch(indexUrl)
+                .then((response) => response.json())
+                .catch((error) => {
+                    console.error("Failed to load the search index", error);
+                    index = null;
+                    return [];
+                });
+        }
+        return index;
+    }
+
+    function score(entry, terms) {
+        let total = 0;
+        for (const term of terms) {
+            let termScore = 0;
+            for (const [field, weight] of Object.entries(WEIGHTS)) {
+                if ((entry[field] || "").toLowerCase().includes(term)) {
+                    termScore += weight;
+                }
+            }
+            // Every term must match somewhere.
+            if (termScore === 0) {
+                return 0;
+            }
+            total += termScore;
+        }
+        return total;
+    }
+
+    function snippet(entry, terms) {
+        const body = entry.body || entry.description || "";
+        const position = Math.max(0, body.toLowerCase().indexOf(terms[0]));
+        const start = Math.max(0, position - SNIPPET_LENGTH / 4);
+        const text = body.slice(start, start + SNIPPET_LENGTH);
+        return (start > 0 ? "…" : "") + text + (start + SNIPPET_LENGTH < body.length ? "…" : "");
+    }
+
+    function render(matches, terms) {
+        results.replaceChildren();
+        for (const entry of matches) {
+            const item = document.createElement("li");
+            const link = document.createElement("a");
+            link.href = new URL(entry.url, indexUrl).href;
+            link.textContent = entry.title;
+            const text = document.createElement("p");
+            text.textContent = snippet(entry, terms);
+            item.append(link, text);
+            results.append(item);
+        }
+    }
+
+    async function search() {
+        const terms = input.value.toLowerCase().split(/\s+/).filter((term) => term.length > 0);
+        if (terms.length === 0) {
+            results.replaceChildren();
+            return;
+        }
+        const entries = await loadIndex();
+        const matches = entries
+            .map((entry) => ({ entry, score: score(entry, terms) }))
+            .filter((match) => match.score > 0)
+            .sort((a, b) => b.score - a.score)
+            .slice(0, MAX_RESULTS)
+            .map((match) => match.entry);
+        render(matches, terms);
+    }
+
+    input.addEventListener("focus", loadIndex, { once: true });
+    input.addEventListener("input", search);
+})();
@@ -0,0 +1,198 @@
+use crate::fs_util::{mime_type, write_if_changed};
+use crate::page_metadata::PageMetadata;
+use crate::rss_generation::{
+    article_author, article_content, article_link, cover_image_file, is_feed_article, keywords,
+    FeedInfo,
+};
+use anyhow::Result;
+use atom_syndication::{
+    CategoryBuilder, ContentBuilder, Entry, EntryBuilder, FeedBuilder, FixedDateTime, LinkBuilder,
+    PersonBuilder,
+};
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::path::Path;
+
+/// Writes an Atom 1.0 feed of the articles to `output_path`.
+pub fn generate_atom(
+    feed: &FeedInfo,
+    metadatas: Vec<PageMetadata>,
+    output_path: &Path,
+) -> Result<()> {
+    let url = feed.url;
+    let entries = articles_to_entries(feed, metadatas);
+    // The feed changes whenever its most recently updated entry does.
+    let updated = entries
+        .iter()
+        .map(|entry| entry.updated)
+        .max()
+        .unwrap_or_default();
+    let file_name = output_path.file_name().unwrap().to_string_lossy();
+
+    let atom_feed = FeedBuilder::default()
+        .title(feed.title.as_str())
+        .id(url)
+        .updated(updated)
+        .lang(Some(feed.language.to_string()))
+        .subtitle(Some(feed.description.into()))
+        .link(
+            LinkBuilder::default()
+                .href(format!("{url}/{file_name}"))
+                .rel("self")
+                .build(),
+        )
+        .link(LinkBuilder::default().href(url).rel("alternate").build())
+        .entries(entries)
+        .build();
+
+    write_if_changed(output_path, atom_feed.to_string())?;
+    Ok(())
+}
+
+fn articles_to_entries(feed: &FeedInfo, metadatas: Vec<PageMetadata>) -> Vec<Entry> {
+    metadatas
+        .into_iter()
+        .filter(is_feed_article)
+        .map(|md| {
+            let link = article_link(feed.url, &md);
+            let published = md.published_at;
+            let updated: FixedDateTime = md
+                .last_modified_at
+                .or(published)
+                .or_else(|| output_modified(feed, &md))
+                .unwrap_or_default();
+            let mut links = vec![LinkBuilder::default()
+                .href(link.as_str())
+                .rel("alternate")
+                .build()];
+            if let Some((url, file)) = cover_image_file(feed, &md) {
+                let length = fs::metadata(&file).map(|m| m.len()).unwrap_or_default();
+                links.push(
+                    LinkBuilder::default()
+                        .href(url)
+                        .rel("enclosure")
+                        .mime_type(Some(mime_type(&file).to_string()))
+                        .length(Some(length.to_string()))
+                        .build(),
+                );
+            }
+            let content = article_content(feed, &md).map(|html| {
+                ContentBuilder::default()
+                    .value(Some(html))
+                    .content_type(Some("html".to_string()))
+                    .build()
+            });
+            // Atom requires an author: without one, the site title stands in.
+            let author = article_author(feed, &md).unwrap_or_else(|| feed.title.clone());
+            let authors = vec![PersonBuilder::default().name(author).build()];
+            let categories = keywords(&md)
+                .map(|keyword| CategoryBuilder::default().term(keyword).build())
+                .collect::<Vec<_>>();
+            EntryBuilder::default()
+                .title(md.title)
+                // The link never changes for a given article, so it doubles as its id.
+                .id(link)
+                .updated(updated)
+                .published(published)
+                .links(links)
+                .authors(authors)
+                .categories(categories)
+                .content(content)
+                .summary(Some(md.description.into()))
+                .build()
+        })
+        .collect()
+}
+
+/// Modification time of the output of an article without any parseable date. Outputs are
+/// only rewritten when they change, so unlike the build time it doesn't change the feed
+/// on every build.
+fn output_modified(feed: &FeedInfo, md: &PageMetadata) -> Option<FixedDateTime> {
+    let path = if md.path.is_empty() {
+        &md.file_name
+    } else {
+        &md.path
+    };
+    let modified = fs::metadata(feed.output_dir.join(path))
+        .ok()?
+        .modified()
+        .ok()?;
+    Some(DateTime::<Utc>::from(modified).fixed_offset())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dates::parse_date;
+    use chrono_tz::Tz;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_articles_to_entries() -> std::io::Result<()> {
+        let tmp_dir = TempDir::with_prefix("atom")?;
+        let output_dir = tmp_dir.path();
+        let feed = FeedInfo {
+            title: "Site".to_string(),
+            url: "https://example.com",
+            description: "A site",
+            language: "en-us",
+            author: Some("Site Author"),
+            full_content: false,
+            output_dir,
+            link_by_file_name: false,
+        };
+        let article = PageMetadata {
+            title: "First Post".to_string(),
+            publish_date: "2024-01-01".to_string(),
+            published_at: parse_date("2024-01-01", Some(Tz::UTC)),
+            last_modified_date: "2024-02-03".to_string(),
+            last_modified_at: parse_date("2024-02-03", Some(Tz::UTC)),
+            description: "About the first post".to_string(),
+            file_name: "first-post.html".to_string(),
+            path: "blog/first-post.html".to_string(),
+            keywords: "rust".to_string(),
+            content_html: "<p>Hello</p>".to_string(),
+            ..Default::default()
+        };
+        let draft = PageMetadata {
+            is_draft: true,
+            ..article.clone()
+        };
+
+        let undated = PageMetadata {
+            publish_date: "someday".to_string(),
+            published_at: None,
+            last_modified_date: String::new(),
+            last_modified_at: None,
+            ..article.clone()
+        };
+        let entries = articles_to_entries(&feed, vec![article, draft]);
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(entry.id, "https://example.com/blog/first-post.html");
+        assert_eq!(entry.links[0].href, entry.id);
+        assert_eq!(entry.updated.to_rfc3339(), "2024-02-03T00:00:00+00:00");
+        assert_eq!(
+            entry.published.map(|date| date.to_rfc3339()),
+            Some("2024-01-01T00:00:00+00:00".to_string())
+        );
+        assert_eq!(entry.authors[0].name, "Site Author");
+        assert_eq!(entry.categories[0].term, "rust");
+        // Only full content feeds carry the article.
+        assert!(entry.content.is_none());
+
+        // Without dates, the entry is updated when its output was last written.
+        let written = parse_date("2025-06-07", Some(Tz::UTC)).unwrap();
+        fs::create_dir_all(output_dir.join("blog"))?;
+        fs::File::create(output_dir.join("blog/first-post.html"))?.set_modified(written.into())?;
+        let feed = FeedInfo {
+            author: None,
+            ..feed
+        };
+        let entries = articles_to_entries(&feed, vec![undated]);
+        assert_eq!(entries[0].updated, written);
+        assert!(entries[0].published.is_none());
+        assert_eq!(entries[0].authors[0].name, "Site");
+        Ok(())
+    }
+}
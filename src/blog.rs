@@ -1,17 +1,19 @@
 use crate::config::GeneretoConfig;
-use crate::jinja_processor::{PageContext, PaginationContext, SiteContext};
+use crate::jinja_processor::{JinjaEnvironment, PageContext, PaginationContext};
 use crate::page_metadata::{PageMetadata, PageMetadataRaw};
 
-use crate::fs_util::copy_directory_recursively;
-use crate::parser::{END_PATTERN, START_PATTERN};
+use crate::build_cache::{self, BuildCache, CompiledPage};
+use crate::fs_util::{copy_directory_recursively, sorted_dir_entries, write_if_changed};
+use crate::parser::{MarkdownOptions, END_PATTERN, START_PATTERN};
 use crate::DraftsOptions;
 use anyhow::Context;
 use chrono::Datelike;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 
-const BLOG_ENTRIES_FOLDER_RELATIVE_PATH: &str = "blog";
+pub(crate) const BLOG_ENTRIES_FOLDER_RELATIVE_PATH: &str = "blog";
 const BLOG_ENTRIES_FILE_NAME: &str = "blog.yml";
 const BLOG_ENTRY_TEMPLATE_FILENAME: &str = "blog.html";
 
@@ -33,7 +35,7 @@ impl BlogEntries {
 /// Given an index filename (e.g. "index.html" or "blog.html") and a page number,
 /// returns the filename for that page.
 /// Page 1 returns the original name; page N >= 2 returns "name-page-N.ext".
-fn get_page_filename(index_name: &Path, page_number: usize) -> PathBuf {
+pub(crate) fn get_page_filename(index_name: &Path, page_number: usize) -> PathBuf {
     if page_number == 1 {
         return index_name.to_path_buf();
     }
@@ -50,6 +52,34 @@ fn get_page_filename(index_name: &Path, page_number: usize) -> PathBuf {
     PathBuf::from(format!("{}-page-{}.{}", stem, page_number, ext))
 }
 
+/// Pagination of page `page_number` out of `total_pages`, for an index named `index_name`.
+pub(crate) fn pagination_context(
+    index_name: &Path,
+    page_number: usize,
+    total_pages: usize,
+) -> PaginationContext {
+    PaginationContext {
+        current_page: page_number,
+        total_pages,
+        has_prev: page_number > 1,
+        has_next: page_number < total_pages,
+        prev_url: if page_number > 1 {
+            get_page_filename(index_name, page_number - 1)
+                .to_string_lossy()
+                .into_owned()
+        } else {
+            String::new()
+        },
+        next_url: if page_number < total_pages {
+            get_page_filename(index_name, page_number + 1)
+                .to_string_lossy()
+                .into_owned()
+        } else {
+            String::new()
+        },
+    }
+}
+
 /// Generates the pagination navigation HTML for marker-based templates.
 fn build_pagination_html(pagination: &PaginationContext) -> String {
     let mut html = String::from("<nav class=\"pagination\">\n");
@@ -73,9 +103,22 @@ fn build_pagination_html(pagination: &PaginationContext) -> String {
     html
 }
 
+/// Convenience wrapper, builds the blog without a build cache.
 pub fn generate_blog(
     genereto_config: &GeneretoConfig,
     drafts_options: &DraftsOptions,
+) -> anyhow::Result<Option<Vec<PageMetadata>>> {
+    let jinja = JinjaEnvironment::from_config(genereto_config);
+    generate_blog_with_cache(genereto_config, drafts_options, jinja.as_ref(), None)
+}
+
+/// Builds the blog articles and the index pages. Articles found in `build_cache` are not
+/// recompiled.
+pub(crate) fn generate_blog_with_cache(
+    genereto_config: &GeneretoConfig,
+    drafts_options: &DraftsOptions,
+    jinja: Option<&JinjaEnvironment>,
+    mut build_cache: Option<&mut BuildCache>,
 ) -> anyhow::Result<Option<Vec<PageMetadata>>> {
     if !should_generate_blog(&genereto_config.content_path) {
         info!(
@@ -88,18 +131,12 @@ pub fn generate_blog(
     debug!("Generating blog");
     fs::create_dir_all(&genereto_config.blog.destination)?;
 
-    // Create site context for Jinja rendering if enabled
-    let site_context = if genereto_config.enable_jinja {
-        Some(SiteContext::new(
-            &genereto_config.title,
-            &genereto_config.url,
-            &genereto_config.description,
-        ))
-    } else {
-        None
-    };
-
-    let mut metadatas = build_articles(genereto_config, drafts_options, site_context.as_ref())?;
+    let mut metadatas = build_articles(
+        genereto_config,
+        drafts_options,
+        jinja,
+        build_cache.as_deref_mut(),
+    )?;
 
     // sort by published date
     metadatas.sort_by(|a, b| a.partial_cmp(b).unwrap());
@@ -110,7 +147,7 @@ pub fn generate_blog(
             genereto_config.blog.base_template
         ))?;
     let template_index_page =
-        crate::parser::process_includes(&template_index_page, &genereto_config.template_dir_path)?;
+        crate::parser::process_includes_in(&template_index_page, &genereto_config.template_dirs)?;
 
     // Filter articles for display (move filtering before pagination)
     let filtered_articles: Vec<&PageMetadata> = metadatas
@@ -129,36 +166,21 @@ pub fn generate_blog(
             let page_filename = get_page_filename(&genereto_config.blog.index_name, page_number);
             let destination_path = genereto_config.blog.destination.join(&page_filename);
 
-            let pagination = PaginationContext {
-                current_page: page_number,
-                total_pages,
-                has_prev: page_number > 1,
-                has_next: page_number < total_pages,
-                prev_url: if page_number > 1 {
-                    get_page_filename(&genereto_config.blog.index_name, page_number - 1)
-                        .to_string_lossy()
-                        .into_owned()
-                } else {
-                    String::new()
-                },
-                next_url: if page_number < total_pages {
-                    get_page_filename(&genereto_config.blog.index_name, page_number + 1)
-                        .to_string_lossy()
-                        .into_owned()
-                } else {
-                    String::new()
-                },
-            };
+            let pagination =
+                pagination_context(&genereto_config.blog.index_name, page_number, total_pages);
 
             build_index_page(
                 template_index_page.clone(),
                 chunk,
                 &destination_path,
                 genereto_config,
-                site_context.as_ref(),
+                jinja,
                 Some(&pagination),
             )
             .with_context(|| format!("Failed to build index page {}.", page_number))?;
+            if let Some(cache) = build_cache.as_deref_mut() {
+                cache.record_output(&destination_path);
+            }
         }
     } else {
         // No pagination - single page with all articles
@@ -172,10 +194,13 @@ pub fn generate_blog(
             &filtered_articles,
             &destination_path,
             genereto_config,
-            site_context.as_ref(),
+            jinja,
             None,
         )
         .context("Failed to build index page.")?;
+        if let Some(cache) = build_cache {
+            cache.record_output(&destination_path);
+        }
     }
 
     Ok(Some(metadatas))
@@ -186,72 +211,105 @@ fn build_index_page(
     articles: &[&PageMetadata],
     destination_path: &Path,
     genereto_config: &GeneretoConfig,
-    site_context: Option<&SiteContext>,
+    jinja: Option<&JinjaEnvironment>,
     pagination: Option<&PaginationContext>,
 ) -> anyhow::Result<()> {
-    let final_content = if let Some(site) = site_context {
+    let final_content = if let Some(jinja) = jinja {
         // Use Jinja2 template rendering
         let page_contexts: Vec<PageContext> = articles
             .iter()
             .map(|md| PageContext::from_page_metadata(md))
             .collect();
-        crate::jinja_processor::render_blog_index(&template_view, site, &page_contexts, pagination)?
+        jinja.render_blog_index(
+            &genereto_config.get_template_name(&genereto_config.blog.base_template),
+            &template_view,
+            &page_contexts,
+            pagination,
+        )?
     } else {
         // Use traditional marker-based rendering
-        let mut template_view = template_view;
+        let title = genereto_config
+            .blog
+            .title
+            .as_ref()
+            .unwrap_or(&genereto_config.title);
+        render_index_markers(
+            template_view,
+            &genereto_config.blog.base_template,
+            articles,
+            title,
+            genereto_config,
+            pagination,
+        )?
+    };
 
-        // Extract the template content between start_content and end_content
-        let start = template_view.find(START_PATTERN).unwrap();
-        let end = template_view.find(END_PATTERN).unwrap();
-        let template_content = &template_view[start + START_PATTERN.len()..end].trim();
+    write_if_changed(destination_path, final_content).context("Failed writing to output page")?;
+    Ok(())
+}
 
-        // If template is empty or just whitespace, fallback to a default template
-        let template_to_use = if template_content.trim().is_empty() {
-            "<div class=\"post\">\n<h2><a href=\"$GENERETO['file_name']\">$GENERETO['title']</a></h2>\n<div class=\"post-date\">$GENERETO['publish_date']</div>\n<p class=\"post-description\">$GENERETO['description']</p>\n</div>\n"
-        } else {
-            template_content
-        };
+/// Renders a marker-based index template: the part between the content markers is repeated
+/// for every article, and the rest of the page gets the site-level placeholders.
+/// `template_path` is only used in the error if the template lacks the markers.
+pub(crate) fn render_index_markers(
+    mut template_view: String,
+    template_path: &Path,
+    articles: &[&PageMetadata],
+    title: &str,
+    genereto_config: &GeneretoConfig,
+    pagination: Option<&PaginationContext>,
+) -> anyhow::Result<String> {
+    // Extract the template content between start_content and end_content
+    let start = template_view.find(START_PATTERN).ok_or_else(|| {
+        anyhow::anyhow!("Start marker `{START_PATTERN}` not found in template {template_path:?}")
+    })?;
+    let end = template_view[start..]
+        .find(END_PATTERN)
+        .map(|end| start + end)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "End marker `{END_PATTERN}` not found after start marker in template \
+                 {template_path:?}"
+            )
+        })?;
+    let template_content = &template_view[start + START_PATTERN.len()..end].trim();
 
-        let mut html_content = String::new();
-        for md in articles {
-            // Apply the template for each article
-            let entry_content = md.apply(template_to_use.to_string());
-            html_content.push_str(&entry_content);
-            html_content.push('\n');
-        }
+    // If template is empty or just whitespace, fallback to a default template
+    let template_to_use = if template_content.trim().is_empty() {
+        "<div class=\"post\">\n<h2><a href=\"$GENERETO['file_name']\">$GENERETO['title']</a></h2>\n<div class=\"post-date\">$GENERETO['publish_date']</div>\n<p class=\"post-description\">$GENERETO['description']</p>\n</div>\n"
+    } else {
+        template_content
+    };
 
-        template_view.replace_range(start..end + END_PATTERN.len(), &html_content);
+    let mut html_content = String::new();
+    for md in articles {
+        // Apply the template for each article
+        let entry_content = md.apply(template_to_use.to_string());
+        html_content.push_str(&entry_content);
+        html_content.push('\n');
+    }
 
-        // Replace pagination placeholder
-        let pagination_html = if let Some(p) = pagination {
-            build_pagination_html(p)
-        } else {
-            String::new()
-        };
-        template_view = template_view.replace("$GENERETO['pagination']", &pagination_html);
+    template_view.replace_range(start..end + END_PATTERN.len(), &html_content);
 
-        // Replace the blog title if configured
-        if let Some(blog_title) = &genereto_config.blog.title {
-            template_view = template_view.replace("$GENERETO['title']", blog_title);
-        } else {
-            template_view = template_view.replace("$GENERETO['title']", &genereto_config.title);
-        }
+    // Replace pagination placeholder
+    let pagination_html = if let Some(p) = pagination {
+        build_pagination_html(p)
+    } else {
+        String::new()
+    };
+    template_view = template_view.replace("$GENERETO['pagination']", &pagination_html);
 
-        // Replace remaining site-level placeholders in the outer template
-        template_view =
-            template_view.replace("$GENERETO['description']", &genereto_config.description);
-        template_view = template_view.replace("$GENERETO['keywords']", "");
-        template_view = template_view.replace("$GENERETO['url']", &genereto_config.url);
-        template_view = template_view.replace(
-            "$GENERETO['current_year']",
-            &chrono::Local::now().year().to_string(),
-        );
+    template_view = template_view.replace("$GENERETO['title']", title);
 
-        template_view
-    };
+    // Replace remaining site-level placeholders in the outer template
+    template_view = template_view.replace("$GENERETO['description']", &genereto_config.description);
+    template_view = template_view.replace("$GENERETO['keywords']", "");
+    template_view = template_view.replace("$GENERETO['url']", &genereto_config.url);
+    template_view = template_view.replace(
+        "$GENERETO['current_year']",
+        &chrono::Local::now().year().to_string(),
+    );
 
-    fs::write(destination_path, final_content).context("Failed writing to output page")?;
-    Ok(())
+    Ok(template_view)
 }
 
 /// Builds the articles to the destination
@@ -259,22 +317,28 @@ fn build_index_page(
 fn build_articles(
     genereto_config: &GeneretoConfig,
     drafts_options: &DraftsOptions,
-    site_context: Option<&SiteContext>,
+    jinja: Option<&JinjaEnvironment>,
+    mut build_cache: Option<&mut BuildCache>,
 ) -> anyhow::Result<Vec<PageMetadata>> {
     debug!("Loading articles metadata");
     let mut articles = vec![];
 
     // Load the default blog template once before the loop
-    let default_template = crate::parser::load_template(
-        &genereto_config.template_dir_path,
-        BLOG_ENTRY_TEMPLATE_FILENAME,
-    )?;
+    let template_dirs = &genereto_config.template_dirs;
+    let default_template =
+        crate::parser::load_template_in(template_dirs, BLOG_ENTRY_TEMPLATE_FILENAME)?;
 
     let default_cover_image = &genereto_config
         .blog
         .default_cover_image
         .clone()
         .unwrap_or_default();
+    let markdown_options = MarkdownOptions::new(genereto_config);
+    let templates_hash = format!(
+        "{}{}",
+        build_cache::hash_templates(template_dirs)?,
+        build_cache::hash_site_collections(jinja)?
+    );
 
     // First try to load from blog.yml if it exists
     let yaml_path = genereto_config.content_path.join(BLOG_ENTRIES_FILE_NAME);
@@ -286,6 +350,8 @@ fn build_articles(
                 &yaml_path,
                 default_cover_image,
                 &genereto_config.url,
+                &markdown_options.extensions,
+                markdown_options.timezone,
             );
             articles.push(metadata);
         }
@@ -299,63 +365,186 @@ fn build_articles(
         return Ok(articles);
     }
 
-    for entry in fs::read_dir(&blog_folder)? {
-        let entry_path = entry?.path();
-        let entry_path_display = entry_path.display().to_string();
+    let mut posts = vec![];
+    for entry_path in sorted_dir_entries(&blog_folder)? {
         let destination_path = genereto_config.get_blog_dest_path(&entry_path);
-        info!("Compiling {entry_path_display} to {destination_path:?}.");
 
         if entry_path.is_dir() {
-            copy_directory_recursively(&entry_path, &destination_path)?;
+            let mut copied = vec![];
+            copy_directory_recursively(&entry_path, &destination_path, &mut copied)?;
+            if let Some(cache) = build_cache.as_deref_mut() {
+                cache.record_assets(copied);
+            }
         } else if entry_path.is_file() && entry_path.extension().unwrap_or_default() == "md" {
+            posts.push((entry_path, destination_path));
+        } else {
+            warn!("Found entry which is not a file nor a directory: {entry_path:?}. Skipping.");
+        }
+    }
+
+    // Articles are compiled in parallel. The results keep the order of `posts`, so the
+    // output doesn't depend on how the work was scheduled.
+    let cache = build_cache.as_deref();
+    let compiled_posts = posts
+        .into_par_iter()
+        .map(|(entry_path, destination_path)| {
+            let entry_path_display = entry_path.display().to_string();
+            info!("Compiling {entry_path_display} to {destination_path:?}.");
+
             // Read source content and parse metadata first to check for custom template
             let source_content = fs::read_to_string(&entry_path)
                 .with_context(|| format!("Failed to read blog post {entry_path_display}"))?;
-            let (intermediate_content, metadata_raw) =
+            let (intermediate_content, mut metadata_raw) =
                 crate::parser::compile_page_phase_1(&source_content)
                     .with_context(|| format!("Failed to parse blog post {entry_path_display}"))?;
 
+            // Expired pages are rendered with the expired template, if there is one.
+            if let Some(expired_template) = &genereto_config.expired_template {
+                if metadata_raw.has_expired(genereto_config.timezone) {
+                    metadata_raw.template_file = Some(expired_template.clone());
+                }
+            }
+
             // Use custom template if specified, otherwise use default
             let template_raw = if let Some(ref template_file) = metadata_raw.template_file {
-                crate::parser::load_template(&genereto_config.template_dir_path, template_file)
-                    .with_context(|| {
+                crate::parser::load_template_in(template_dirs, template_file).with_context(
+                    || {
                         format!(
                             "Blog post '{}' specifies template '{}' which could not be loaded",
                             entry_path_display, template_file
                         )
-                    })?
+                    },
+                )?
             } else {
                 default_template.clone()
             };
 
-            // Compile phase 2 with the selected template
-            let (content, metadata) = crate::parser::compile_page_phase_2(
-                intermediate_content,
-                &template_raw,
-                metadata_raw,
-                default_cover_image,
-                &entry_path,
-                &genereto_config.url,
-                site_context,
-            )
-            .with_context(|| format!("Failed to compile blog post {entry_path_display}"))?;
-
-            // Handle drafts and write output if generating single pages
-            if metadata.is_draft && drafts_options.is_hide() {
-                continue;
+            let template_name = metadata_raw
+                .template_file
+                .clone()
+                .unwrap_or_else(|| BLOG_ENTRY_TEMPLATE_FILENAME.to_string());
+            let source_hash = build_cache::hash(source_content.as_bytes());
+            let template_hash =
+                build_cache::hash(format!("{template_raw}{templates_hash}").as_bytes());
+            let cached =
+                cache.and_then(|cache| cache.get(&entry_path, &source_hash, &template_hash));
+            let mut metadata = match cached {
+                Some(metadata) => metadata,
+                None => {
+                    // Compile phase 2 with the selected template
+                    let (content, metadata) = crate::parser::compile_page_phase_2(
+                        intermediate_content,
+                        &template_raw,
+                        &template_name,
+                        metadata_raw,
+                        default_cover_image,
+                        &entry_path,
+                        &genereto_config.url,
+                        jinja,
+                        &markdown_options,
+                    )
+                    .with_context(|| format!("Failed to compile blog post {entry_path_display}"))?;
+
+                    // Handle drafts and write output if generating single pages
+                    if metadata.is_draft && drafts_options.is_hide() {
+                        return Ok(None);
+                    }
+
+                    if genereto_config.blog.generate_single_pages {
+                        fs::write(&destination_path, content).with_context(|| {
+                            format!("Failed to write blog post to {destination_path:?}")
+                        })?;
+                    }
+                    metadata
+                }
+            };
+            if genereto_config.blog.generate_single_pages {
+                metadata.path = genereto_config.get_output_relative_path(&destination_path);
             }
-
+            Ok(Some(CompiledPage {
+                source: entry_path,
+                source_hash,
+                template_hash,
+                destination: destination_path,
+                metadata,
+            }))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    for post in compiled_posts.into_iter().flatten() {
+        articles.push(post.metadata.clone());
+        if let Some(cache) = build_cache.as_deref_mut() {
             if genereto_config.blog.generate_single_pages {
-                fs::write(&destination_path, content).with_context(|| {
-                    format!("Failed to write blog post to {destination_path:?}")
-                })?;
+                cache.insert(post);
             }
+        }
+    }
+    Ok(articles)
+}
 
-            articles.push(metadata);
-        } else {
-            warn!("Found entry which is not a file nor a directory: {entry_path:?}. Skipping.");
+/// Metadata of the articles without compiling them, newest first like in the index pages.
+/// Each article comes with its source file, `blog.yml` for the entries listed there.
+pub(crate) fn load_articles_metadata(
+    genereto_config: &GeneretoConfig,
+    markdown_options: &MarkdownOptions,
+) -> anyhow::Result<Vec<(PathBuf, PageMetadata)>> {
+    let mut articles = vec![];
+    if !should_generate_blog(&genereto_config.content_path) {
+        return Ok(articles);
+    }
+    let default_cover_image = &genereto_config
+        .blog
+        .default_cover_image
+        .clone()
+        .unwrap_or_default();
+
+    let yaml_path = genereto_config.content_path.join(BLOG_ENTRIES_FILE_NAME);
+    if let Some(blog_entries) = BlogEntries::load_from_path(&yaml_path)? {
+        for entry in blog_entries.entries {
+            let metadata = PageMetadata::new(
+                entry,
+                "",
+                &yaml_path,
+                default_cover_image,
+                &genereto_config.url,
+                &markdown_options.extensions,
+                markdown_options.timezone,
+            );
+            articles.push((yaml_path.clone(), metadata));
         }
     }
+
+    let blog_folder = genereto_config
+        .content_path
+        .join(BLOG_ENTRIES_FOLDER_RELATIVE_PATH);
+    if blog_folder.exists() {
+        let posts: Vec<PathBuf> = sorted_dir_entries(&blog_folder)?
+            .into_iter()
+            .filter(|entry_path| {
+                entry_path.is_file() && entry_path.extension().unwrap_or_default() == "md"
+            })
+            .collect();
+        let posts = posts
+            .into_par_iter()
+            .map(|entry_path| {
+                let mut metadata = crate::parser::load_page_metadata(
+                    &entry_path,
+                    default_cover_image,
+                    &genereto_config.url,
+                    markdown_options,
+                )?;
+                if genereto_config.blog.generate_single_pages {
+                    metadata.path = genereto_config
+                        .get_output_relative_path(&genereto_config.get_blog_dest_path(&entry_path));
+                }
+                Ok((entry_path, metadata))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        articles.extend(posts);
+    }
+
+    // sort by published date
+    articles.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap());
     Ok(articles)
 }
 
@@ -370,7 +559,7 @@ fn should_generate_blog(content_path: &Path) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::GeneretoConfigBlog;
+    use crate::{FeedFormat, GeneretoConfigBlog};
     use std::io;
     use tempfile::TempDir;
 
@@ -432,38 +621,56 @@ entries:
         let article1 = PageMetadata {
             title: "Test Article 1".to_string(),
             publish_date: "2024-01-01".to_string(),
+            published_at: None,
+            expiry_date: String::new(),
+            expires_at: None,
             keywords: "test".to_string(),
             reading_time_mins: "5".to_string(),
             description: "Test description 1".to_string(),
             file_name: "article1.html".to_string(),
             page_name: "article1".to_string(),
+            path: "article1.html".to_string(),
             table_of_contents: "".to_string(),
             last_modified_date: "2024-01-01".to_string(),
+            last_modified_at: None,
             cover_image: "cover1.jpg".to_string(),
             is_draft: false,
             add_title: false,
             article_url: None,
             website_url: "test.com".to_string(),
             template_file: None,
+            exclude_from_sitemap: false,
+            sitemap_priority: None,
             custom_metadata: Default::default(),
+            content_html: String::new(),
+            content_text: String::new(),
         };
         let article2 = PageMetadata {
             title: "Test Article 2".to_string(),
             publish_date: "2024-01-02".to_string(),
+            published_at: None,
+            expiry_date: String::new(),
+            expires_at: None,
             keywords: "test2".to_string(),
             reading_time_mins: "3".to_string(),
             description: "Test description 2".to_string(),
             file_name: "article2.html".to_string(),
             page_name: "article2".to_string(),
+            path: "article2.html".to_string(),
             table_of_contents: "".to_string(),
             last_modified_date: "2024-01-02".to_string(),
+            last_modified_at: None,
             cover_image: "cover2.jpg".to_string(),
             is_draft: false,
             add_title: false,
             article_url: None,
             website_url: "test.com".to_string(),
             template_file: None,
+            exclude_from_sitemap: false,
+            sitemap_priority: None,
             custom_metadata: Default::default(),
+            content_html: String::new(),
+            content_text: String::new(),
         };
         let articles: Vec<&PageMetadata> = vec![&article1, &article2];
 
@@ -495,6 +702,19 @@ entries:
                 default_cover_image: Some("cover.jpg".into()),
                 max_entries_per_page: None,
             },
+            taxonomies: vec![],
+            feeds: vec![FeedFormat::Rss],
+            language: "en-us".into(),
+            author: None,
+            feed_full_content: false,
+            sitemap: true,
+            robots_txt: None,
+            search_index: false,
+            highlight: None,
+            markdown: Default::default(),
+            timezone: None,
+            expired_template: None,
+            template_dirs: vec!["template".into()],
         };
 
         build_index_page(
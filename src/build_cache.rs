@@ -0,0 +1,300 @@
+use crate::fs_util::{clear_directory, sorted_dir_entries};
+use crate::git_history;
+use crate::jinja_processor::JinjaEnvironment;
+use crate::page_metadata::PageMetadata;
+use crate::{DraftsOptions, GeneretoConfig};
+use anyhow::{bail, Context};
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const CACHE_FILENAME: &str = ".genereto-cache.yml";
+
+/// Manifest stored in the output directory, describing what the previous build produced.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    /// Hash of everything that affects every page: the config, the drafts option,
+    /// the genereto version and the current year.
+    config_hash: String,
+    /// Compiled pages, keyed by their source path.
+    pages: BTreeMap<PathBuf, CacheEntry>,
+    /// Other generated files, like index pages and feeds.
+    #[serde(default)]
+    outputs: BTreeSet<PathBuf>,
+    /// Files copied from the content, template and theme folders.
+    #[serde(default)]
+    assets: BTreeSet<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    source_hash: String,
+    template_hash: String,
+    /// Date of the last commit of the source. Commits change the last modified date of the
+    /// page without changing its source.
+    #[serde(default)]
+    last_commit_date: Option<String>,
+    destination: PathBuf,
+    metadata: PageMetadata,
+}
+
+/// A page of the current build, with the hashes it was built from.
+pub(crate) struct CompiledPage {
+    pub(crate) source: PathBuf,
+    pub(crate) source_hash: String,
+    pub(crate) template_hash: String,
+    pub(crate) destination: PathBuf,
+    pub(crate) metadata: PageMetadata,
+}
+
+/// Lets a build skip pages whose source and template didn't change since the previous build.
+///
+/// Pages are recompiled when their source, their template or the config changed. Draft pages
+/// are never reused, because their draft status can depend on today's date.
+pub(crate) struct BuildCache {
+    path: PathBuf,
+    config_hash: String,
+    previous: BTreeMap<PathBuf, CacheEntry>,
+    current: BTreeMap<PathBuf, CacheEntry>,
+    previous_outputs: BTreeSet<PathBuf>,
+    current_outputs: BTreeSet<PathBuf>,
+    previous_assets: BTreeSet<PathBuf>,
+    current_assets: BTreeSet<PathBuf>,
+    /// If true, the manifest keeps the html of the pages, for full content feeds.
+    keep_content_html: bool,
+    /// If true, the manifest keeps the text of the pages, for the search index.
+    keep_content_text: bool,
+}
+
+impl BuildCache {
+    /// Loads the manifest of the previous build. If it is missing, unreadable or it was
+    /// built with a different config, the output directory is wiped to start from scratch.
+    /// Only output directories of a previous build are wiped: a directory with other files
+    /// and no manifest is an error.
+    pub(crate) fn load(
+        genereto_config: &GeneretoConfig,
+        drafts_options: &DraftsOptions,
+    ) -> anyhow::Result<Self> {
+        let output_dir = &genereto_config.output_dir_path;
+        let path = output_dir.join(CACHE_FILENAME);
+        let config_hash = hash(
+            format!(
+                "{genereto_config:?}{drafts_options:?}{}{}",
+                env!("CARGO_PKG_VERSION"),
+                chrono::Local::now().year()
+            )
+            .as_bytes(),
+        );
+
+        let manifest = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_yaml_ng::from_str::<Manifest>(&content).ok())
+            .filter(|manifest| manifest.config_hash == config_hash);
+        let manifest = match manifest {
+            Some(manifest) => {
+                debug!("Reusing build cache with {} pages.", manifest.pages.len());
+                manifest
+            }
+            None => {
+                info!("No usable build cache found, doing a full build.");
+                if output_dir.exists() {
+                    let is_empty = sorted_dir_entries(output_dir)?
+                        .iter()
+                        .all(|entry| entry.file_name().unwrap() == ".git");
+                    if !is_empty && !path.exists() {
+                        bail!(
+                            "The output folder {output_dir:?} has files which were not written \
+                             by genereto, since there is no {CACHE_FILENAME}. Empty it or choose \
+                             another output folder, builds delete what is in it."
+                        );
+                    }
+                    clear_directory(output_dir)?;
+                }
+                // An empty manifest marks the folder as genereto's, so the next build can
+                // clear it even if this one fails halfway.
+                fs::create_dir_all(output_dir)?;
+                fs::write(&path, serde_yaml_ng::to_string(&Manifest::default())?)?;
+                Manifest::default()
+            }
+        };
+        fs::create_dir_all(output_dir)?;
+
+        Ok(Self {
+            path,
+            config_hash,
+            previous: manifest.pages,
+            current: BTreeMap::new(),
+            previous_outputs: manifest.outputs,
+            current_outputs: BTreeSet::new(),
+            previous_assets: manifest.assets,
+            current_assets: BTreeSet::new(),
+            keep_content_html: genereto_config.feed_full_content,
+            keep_content_text: genereto_config.search_index,
+        })
+    }
+
+    /// Returns the metadata of `source` from the previous build, if neither the source, its
+    /// last commit nor the template changed and its output is still there.
+    pub(crate) fn get(
+        &self,
+        source: &Path,
+        source_hash: &str,
+        template_hash: &str,
+    ) -> Option<PageMetadata> {
+        let entry = self.previous.get(source)?;
+        if entry.source_hash != source_hash
+            || entry.template_hash != template_hash
+            || entry.last_commit_date != git_history::last_modified_date(source)
+            || entry.metadata.is_draft
+            || entry.metadata.has_expired()
+            || !entry.destination.exists()
+        {
+            return None;
+        }
+        debug!("Page {source:?} didn't change, skipping it.");
+        Some(entry.metadata.clone())
+    }
+
+    /// Records a page of this build, either compiled or reused from the previous one.
+    /// The content of the page is only kept if the feeds or the search index need it, so
+    /// the manifest doesn't grow as large as the website.
+    pub(crate) fn insert(&mut self, page: CompiledPage) {
+        let mut metadata = page.metadata;
+        if !self.keep_content_html {
+            metadata.content_html.clear();
+        }
+        if !self.keep_content_text {
+            metadata.content_text.clear();
+        }
+        self.current.insert(
+            page.source.clone(),
+            CacheEntry {
+                source_hash: page.source_hash,
+                template_hash: page.template_hash,
+                last_commit_date: git_history::last_modified_date(&page.source),
+                destination: page.destination,
+                metadata,
+            },
+        );
+    }
+
+    /// Records a generated file which doesn't come from a single page, like an index page.
+    /// Files recorded by the previous build but not by this one are removed on save.
+    pub(crate) fn record_output(&mut self, path: &Path) {
+        self.current_outputs.insert(path.to_path_buf());
+    }
+
+    /// Records the files copied as they are, like images and stylesheets. Like outputs,
+    /// assets whose source is gone are removed on save.
+    pub(crate) fn record_assets(&mut self, paths: Vec<PathBuf>) {
+        self.current_assets.extend(paths);
+    }
+
+    /// Files recorded with `record_output` by this build so far.
+    pub(crate) fn outputs(&self) -> impl Iterator<Item = &Path> {
+        self.current_outputs.iter().map(PathBuf::as_path)
+    }
+
+    /// Removes the outputs and assets of the previous build which this one didn't write,
+    /// and the output of pages which are gone, then writes the manifest for the next build.
+    pub(crate) fn save(self) -> anyhow::Result<()> {
+        // A file can be written in another way than before, like a robots.txt which is not
+        // in the content anymore and is generated instead.
+        let written: BTreeSet<&PathBuf> = self
+            .current_outputs
+            .iter()
+            .chain(&self.current_assets)
+            .chain(self.current.values().map(|entry| &entry.destination))
+            .collect();
+        let stale = self
+            .previous_outputs
+            .iter()
+            .chain(&self.previous_assets)
+            .filter(|path| !written.contains(path));
+        for output in stale {
+            if output.exists() {
+                info!("Removing {output:?}, it is not generated anymore.");
+                fs::remove_file(output)?;
+            }
+        }
+        for (source, entry) in &self.previous {
+            let still_written = self
+                .current
+                .values()
+                .any(|current| current.destination == entry.destination);
+            if !self.current.contains_key(source) && !still_written && entry.destination.exists() {
+                info!(
+                    "Removing {:?}, its source {source:?} is gone.",
+                    entry.destination
+                );
+                fs::remove_file(&entry.destination)?;
+            }
+        }
+        let manifest = Manifest {
+            config_hash: self.config_hash,
+            pages: self.current,
+            outputs: self.current_outputs,
+            assets: self.current_assets,
+        };
+        fs::write(&self.path, serde_yaml_ng::to_string(&manifest)?)?;
+        Ok(())
+    }
+}
+
+/// 64 bit FNV-1a hash, as hex string. Stable across builds and platforms, unlike the std hasher.
+pub(crate) fn hash(bytes: &[u8]) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    let hash = bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    });
+    format!("{hash:016x}")
+}
+
+/// Hash of every file in the template folder and the themes. Besides their own template,
+/// pages depend on the templates it extends or includes, on the shortcodes and, with Jinja,
+/// on the assets they fingerprint.
+pub(crate) fn hash_templates(template_dirs: &[PathBuf]) -> anyhow::Result<String> {
+    fn collect_templates(dir: &Path, templates: &mut Vec<u8>) -> anyhow::Result<()> {
+        for entry_path in sorted_dir_entries(dir)? {
+            if entry_path.is_dir() {
+                collect_templates(&entry_path, templates)?;
+            } else {
+                let template = fs::read(&entry_path)
+                    .with_context(|| format!("Failed to read the template {entry_path:?}"))?;
+                templates.extend(format!("{entry_path:?}").as_bytes());
+                templates.extend(template);
+            }
+        }
+        Ok(())
+    }
+    let mut templates = vec![];
+    for template_dir in template_dirs.iter().filter(|dir| dir.is_dir()) {
+        collect_templates(template_dir, &mut templates)?;
+    }
+    Ok(hash(&templates))
+}
+
+/// Hash of the collections of the website available to Jinja templates. Any page can list
+/// the others, so all of them depend on it.
+pub(crate) fn hash_site_collections(jinja: Option<&JinjaEnvironment>) -> anyhow::Result<String> {
+    match jinja {
+        Some(jinja) => Ok(hash(serde_json::to_string(&jinja.site)?.as_bytes())),
+        None => Ok(String::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash() {
+        // Reference values of FNV-1a 64.
+        assert_eq!(hash(b""), "cbf29ce484222325");
+        assert_eq!(hash(b"a"), "af63dc4c8601ec8c");
+        assert_ne!(hash(b"page 1"), hash(b"page 2"));
+    }
+}
@@ -1,11 +1,16 @@
 mod raw;
 
-use crate::config::raw::GeneretoConfigRaw;
-use std::path::{Path, PathBuf};
+use crate::config::raw::{GeneretoConfigRaw, GeneretoConfigTaxonomyRaw};
+use crate::parser::find_template;
+use anyhow::{anyhow, bail};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
 
+/// Default output folder, inside the project folder.
 const OUTPUT_DIR: &str = "output";
 
-const CONFIG_FILENAME: &str = "config.yml";
+pub(crate) const CONFIG_FILENAME: &str = "config.yml";
 // folder name for the markdown files with the content of the generated website
 const CONTENT: &str = "content";
 // Template folder name
@@ -30,6 +35,135 @@ pub struct GeneretoConfig {
     /// Enable Jinja2 template processing with minijinja
     pub enable_jinja: bool,
     pub blog: GeneretoConfigBlog,
+    pub taxonomies: Vec<GeneretoConfigTaxonomy>,
+    /// Feeds generated for the blog.
+    pub feeds: Vec<FeedFormat>,
+    /// Language of the website, used in feeds.
+    pub language: String,
+    /// Default author of the articles, used in feeds.
+    pub author: Option<String>,
+    /// If true, feeds include the whole content of the articles.
+    pub feed_full_content: bool,
+    /// If true, writes sitemap.xml.
+    pub sitemap: bool,
+    /// Rules of robots.txt. Allows everything if missing.
+    pub robots_txt: Option<String>,
+    /// If true, writes search-index.json.
+    pub search_index: bool,
+    /// Highlighting of code blocks. Code is left as it is if None.
+    pub highlight: Option<GeneretoConfigHighlight>,
+    /// Optional markdown extensions, overridable per page.
+    pub markdown: GeneretoConfigMarkdown,
+    /// Time zone of the dates without an offset, like `publish_date: 2024-01-15 10:30`.
+    /// The local time zone if None.
+    pub timezone: Option<Tz>,
+    /// Template of the pages past their `expiry_date`. Expired pages are hidden like drafts
+    /// if None.
+    pub expired_template: Option<String>,
+    /// Folders where templates are looked up: `template_dir_path` first, then the themes
+    /// in order, for the templates it doesn't have.
+    pub template_dirs: Vec<PathBuf>,
+}
+
+/// Feed formats which can be generated for the blog.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedFormat {
+    /// RSS 2.0, written to rss.xml
+    Rss,
+    /// Atom 1.0, written to atom.xml
+    Atom,
+    /// JSON Feed 1.1, written to feed.json
+    Json,
+}
+
+impl FeedFormat {
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "rss.xml",
+            FeedFormat::Atom => "atom.xml",
+            FeedFormat::Json => "feed.json",
+        }
+    }
+}
+
+/// Build time highlighting of fenced code blocks.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GeneretoConfigHighlight {
+    /// Name of one of the themes bundled with syntect, like "InspiredGitHub".
+    #[serde(default = "default_highlight_theme")]
+    pub theme: String,
+    #[serde(default)]
+    pub style: HighlightStyle,
+    /// If true, every code block gets line numbers. Blocks can opt in with `linenos`.
+    #[serde(default)]
+    pub line_numbers: bool,
+}
+
+fn default_highlight_theme() -> String {
+    "InspiredGitHub".into()
+}
+
+/// How the colors of highlighted code are applied.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HighlightStyle {
+    /// Spans with css classes. The theme is written to highlight.css.
+    #[default]
+    Classes,
+    /// Spans with inline styles.
+    Inline,
+}
+
+/// Optional markdown extensions of pulldown-cmark, all disabled by default.
+/// Tables, strikethrough and heading attributes are always enabled.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct GeneretoConfigMarkdown {
+    /// Footnote references like `[^1]` and their definitions.
+    pub footnotes: bool,
+    /// Task list items like `- [x] done`.
+    pub tasklists: bool,
+    /// Curly quotes, en and em dashes and ellipses.
+    pub smart_punctuation: bool,
+    /// Definition lists: a term followed by `: definition`.
+    pub definition_lists: bool,
+    /// Inline `$math$` and display `$$math$$`, rendered as spans for a client side library.
+    pub math: bool,
+    /// GitHub flavored alerts like `> [!NOTE]`.
+    pub gfm: bool,
+    /// YAML (`---`) and TOML (`+++`) metadata blocks, which are left out of the page.
+    pub metadata_blocks: bool,
+}
+
+impl GeneretoConfigMarkdown {
+    /// Applies the extensions set in the frontmatter of a page.
+    pub(crate) fn with_overrides(self, overrides: &GeneretoConfigMarkdownOverrides) -> Self {
+        Self {
+            footnotes: overrides.footnotes.unwrap_or(self.footnotes),
+            tasklists: overrides.tasklists.unwrap_or(self.tasklists),
+            smart_punctuation: overrides
+                .smart_punctuation
+                .unwrap_or(self.smart_punctuation),
+            definition_lists: overrides.definition_lists.unwrap_or(self.definition_lists),
+            math: overrides.math.unwrap_or(self.math),
+            gfm: overrides.gfm.unwrap_or(self.gfm),
+            metadata_blocks: overrides.metadata_blocks.unwrap_or(self.metadata_blocks),
+        }
+    }
+}
+
+/// The `markdown` section of a page frontmatter. Missing fields keep the value from the config.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct GeneretoConfigMarkdownOverrides {
+    pub footnotes: Option<bool>,
+    pub tasklists: Option<bool>,
+    pub smart_punctuation: Option<bool>,
+    pub definition_lists: Option<bool>,
+    pub math: Option<bool>,
+    pub gfm: Option<bool>,
+    pub metadata_blocks: Option<bool>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -44,14 +178,14 @@ pub struct GeneretoConfigBlog {
 }
 impl GeneretoConfigBlog {
     fn new_from_raw(
-        project_path: &Path,
+        output_dir_path: &Path,
         raw_config: &GeneretoConfigRaw,
-        template_base_path: &Path,
+        template_dirs: &[PathBuf],
     ) -> Self {
         let blog_raw = &raw_config.blog;
-        let base_template = template_base_path.join(&blog_raw.base_template);
+        let base_template = find_template(template_dirs, &blog_raw.base_template);
 
-        let destination = project_path.join(OUTPUT_DIR).join(&blog_raw.destination);
+        let destination = output_dir_path.join(&blog_raw.destination);
 
         Self {
             base_template,
@@ -65,6 +199,46 @@ impl GeneretoConfigBlog {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneretoConfigTaxonomy {
+    /// Name of the taxonomy, e.g. "tags". Pages list their terms in the frontmatter field
+    /// with the same name, and the listing pages are written to the folder with this name.
+    pub name: String,
+    pub template: PathBuf,
+    pub index_template: PathBuf,
+    /// Path of the RSS feed of each term relative to the output folder, with a `{term}`
+    /// placeholder.
+    pub feed_path: String,
+}
+impl GeneretoConfigTaxonomy {
+    fn new_from_raw(
+        raw_taxonomy: &GeneretoConfigTaxonomyRaw,
+        template_dirs: &[PathBuf],
+        blog: &GeneretoConfigBlog,
+    ) -> Self {
+        let template = raw_taxonomy
+            .template
+            .as_ref()
+            .map(|template| find_template(template_dirs, template))
+            .unwrap_or_else(|| blog.base_template.clone());
+        let index_template = raw_taxonomy
+            .index_template
+            .as_ref()
+            .map(|template| find_template(template_dirs, template))
+            .unwrap_or_else(|| template.clone());
+        let feed_path = raw_taxonomy
+            .feed_path
+            .clone()
+            .unwrap_or_else(|| format!("{}/{{term}}/rss.xml", raw_taxonomy.name));
+        Self {
+            name: raw_taxonomy.name.clone(),
+            template,
+            index_template,
+            feed_path,
+        }
+    }
+}
+
 impl GeneretoConfig {
     pub fn load_from_folder<P: AsRef<Path>>(project_path: P) -> anyhow::Result<Self> {
         let project_path = project_path.as_ref().to_path_buf();
@@ -81,12 +255,38 @@ impl GeneretoConfig {
             project_path.join(TEMPLATES)
         }
         .join(&raw_config.template);
+        // join returns the theme as is, if it's absolute.
+        let template_dirs: Vec<PathBuf> = std::iter::once(template_dir_path.clone())
+            .chain(
+                raw_config
+                    .themes
+                    .iter()
+                    .map(|theme| project_path.join(theme)),
+            )
+            .collect();
+
+        // join returns output_dirname as is, if it's absolute.
+        let output_dir_path = project_path.join(&raw_config.output_dirname);
+        let blog = GeneretoConfigBlog::new_from_raw(&output_dir_path, &raw_config, &template_dirs);
 
-        let blog = GeneretoConfigBlog::new_from_raw(&project_path, &raw_config, &template_dir_path);
+        let taxonomies = raw_config
+            .taxonomies
+            .iter()
+            .map(|taxonomy| GeneretoConfigTaxonomy::new_from_raw(taxonomy, &template_dirs, &blog))
+            .collect();
+
+        let timezone = raw_config
+            .timezone
+            .as_deref()
+            .map(|timezone| {
+                timezone.parse::<Tz>().map_err(|_| {
+                    anyhow!("Unknown timezone '{timezone}', expected a name like Europe/Rome")
+                })
+            })
+            .transpose()?;
 
-        let output_dir_path = project_path.join(OUTPUT_DIR);
         let content_path = project_path.join(CONTENT);
-        Ok(Self {
+        let config = Self {
             template_dir_path,
             output_dir_path,
             content_path,
@@ -98,26 +298,165 @@ impl GeneretoConfig {
             description: raw_config.description,
             enable_jinja: raw_config.enable_jinja,
             blog,
-        })
+            taxonomies,
+            feeds: raw_config.feeds,
+            language: raw_config.language,
+            author: raw_config.author,
+            feed_full_content: raw_config.feed_full_content,
+            sitemap: raw_config.sitemap,
+            robots_txt: raw_config.robots_txt,
+            search_index: raw_config.search_index,
+            highlight: raw_config.highlight,
+            markdown: raw_config.markdown,
+            timezone,
+            expired_template: raw_config.expired_template,
+            template_dirs,
+        };
+        config.check_output_dir()?;
+        Ok(config)
+    }
+
+    /// Writes the website to `output_dir_path` instead of the configured output folder.
+    /// The blog destination moves along with it.
+    pub fn with_output_dir<P: AsRef<Path>>(mut self, output_dir_path: P) -> anyhow::Result<Self> {
+        let output_dir_path = output_dir_path.as_ref().to_path_buf();
+        if let Ok(blog_destination) = self.blog.destination.strip_prefix(&self.output_dir_path) {
+            self.blog.destination = output_dir_path.join(blog_destination);
+        }
+        self.output_dir_path = output_dir_path;
+        self.check_output_dir()?;
+        Ok(self)
+    }
+
+    /// Full builds empty the output folder, so it can't be, or contain, the project, the
+    /// content or the templates. It can't be inside the content or the templates either,
+    /// or builds would copy it into itself.
+    fn check_output_dir(&self) -> anyhow::Result<()> {
+        let output_dir = resolve_path(&self.output_dir_path);
+        if resolve_path(&self.project_path).starts_with(&output_dir) {
+            bail!(
+                "The output folder {:?} contains the project folder {:?}. Builds delete what \
+                 is in the output folder, choose another one.",
+                self.output_dir_path,
+                self.project_path
+            );
+        }
+        let sources = [
+            ("content", &self.content_path),
+            ("template", &self.template_dir_path),
+        ]
+        .into_iter()
+        .chain(
+            self.template_dirs
+                .iter()
+                .skip(1)
+                .map(|theme| ("theme", theme)),
+        );
+        for (name, path) in sources {
+            let source = resolve_path(path);
+            if source.starts_with(&output_dir) {
+                bail!(
+                    "The output folder {:?} contains the {name} folder {path:?}. Builds delete \
+                     what is in the output folder, choose another one.",
+                    self.output_dir_path
+                );
+            }
+            if output_dir.starts_with(&source) {
+                bail!(
+                    "The output folder {:?} is inside the {name} folder {path:?}. Builds copy \
+                     the {name} folder to the output folder, choose another one.",
+                    self.output_dir_path
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Path of the RSS feed of `term` in the output folder.
+    pub(crate) fn get_feed_path(&self, taxonomy: &GeneretoConfigTaxonomy, term: &str) -> PathBuf {
+        self.output_dir_path
+            .join(taxonomy.feed_path.replace("{term}", term))
+    }
+
+    /// Path of `destination_path` relative to the output folder, with "/" separators so it
+    /// can be used in links.
+    pub(crate) fn get_output_relative_path(&self, destination_path: &Path) -> String {
+        destination_path
+            .strip_prefix(&self.output_dir_path)
+            .unwrap_or(destination_path)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Name of a template for Jinja2: its path relative to the template or theme folder.
+    pub(crate) fn get_template_name(&self, template_path: &Path) -> String {
+        self.template_dirs
+            .iter()
+            .find_map(|template_dir| template_path.strip_prefix(template_dir).ok())
+            .unwrap_or(template_path)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
     }
 
     pub fn get_blog_dest_path(&self, entry_path: &Path) -> PathBuf {
-        self.inner_get_dest_path(entry_path, &self.blog.destination)
+        let name = entry_path.file_name().unwrap();
+        Self::inner_get_dest_path(entry_path, Path::new(name), &self.blog.destination)
     }
 
     /// Given a file or directory, returns the final destination path in output directory.
+    /// Entries nested inside the content folder keep their relative path.
     pub fn get_dest_path(&self, entry_path: &Path) -> PathBuf {
-        self.inner_get_dest_path(entry_path, &self.output_dir_path)
+        let relative_path = entry_path
+            .strip_prefix(&self.content_path)
+            .unwrap_or_else(|_| Path::new(entry_path.file_name().unwrap()));
+        Self::inner_get_dest_path(entry_path, relative_path, &self.output_dir_path)
     }
-    fn inner_get_dest_path(&self, entry_path: &Path, base_path: &Path) -> PathBuf {
+    fn inner_get_dest_path(entry_path: &Path, relative_path: &Path, base_path: &Path) -> PathBuf {
         debug!("entry path: {entry_path:?}");
 
-        let name = entry_path.file_name().unwrap().to_str().unwrap();
-        // unwraps needed because these returns optional
-        base_path.join(if entry_path.is_dir() {
-            name.to_string()
+        let destination = base_path.join(relative_path);
+        if !entry_path.is_dir() && entry_path.extension().unwrap_or_default() == "md" {
+            destination.with_extension("html")
         } else {
-            name.replace(".md", ".html")
-        })
+            destination
+        }
+    }
+}
+
+/// Absolute path of `path` with `..` and symbolic links resolved, so paths can be compared.
+/// The missing part of the path, if any, is appended to its closest existing ancestor.
+fn resolve_path(path: &Path) -> PathBuf {
+    let mut absolute = PathBuf::new();
+    for component in std::path::absolute(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .components()
+    {
+        match component {
+            Component::ParentDir => {
+                absolute.pop();
+            }
+            component => absolute.push(component),
+        }
+    }
+    let mut existing = absolute.as_path();
+    let mut missing = vec![];
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return missing
+                .iter()
+                .rev()
+                .fold(canonical, |path, name| path.join(name));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                existing = parent;
+            }
+            _ => return absolute,
+        }
     }
 }
@@ -1,4 +1,6 @@
-use crate::config::CONFIG_FILENAME;
+use crate::config::{
+    FeedFormat, GeneretoConfigHighlight, GeneretoConfigMarkdown, CONFIG_FILENAME, OUTPUT_DIR,
+};
 use anyhow::bail;
 use serde::{Deserialize, Serialize};
 use std::fs::File;
@@ -22,6 +24,22 @@ pub(crate) struct GeneretoConfigBlogRaw {
     pub(crate) max_entries_per_page: Option<usize>,
 }
 
+/// A taxonomy like tags or categories, read from the frontmatter field with the same name.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct GeneretoConfigTaxonomyRaw {
+    pub(crate) name: String,
+    /// Template of the term pages, defaults to the blog base template.
+    #[serde(default)]
+    pub(crate) template: Option<PathBuf>,
+    /// Template of the page listing all the terms, defaults to `template`.
+    #[serde(default)]
+    pub(crate) index_template: Option<PathBuf>,
+    /// Where to write the RSS feed of each term, relative to the output folder. `{term}` is
+    /// replaced with the term. Defaults to `<name>/{term}/rss.xml`.
+    #[serde(default)]
+    pub(crate) feed_path: Option<String>,
+}
+
 fn index_html() -> PathBuf {
     "index.html".into()
 }
@@ -29,6 +47,22 @@ fn blog_destination() -> PathBuf {
     "".into()
 }
 
+fn output_dirname() -> PathBuf {
+    OUTPUT_DIR.into()
+}
+
+fn default_feeds() -> Vec<FeedFormat> {
+    vec![FeedFormat::Rss]
+}
+
+fn default_language() -> String {
+    "en-us".into()
+}
+
+fn default_sitemap() -> bool {
+    true
+}
+
 fn default_single_pages() -> bool {
     true
 }
@@ -47,13 +81,16 @@ impl Default for GeneretoConfigBlogRaw {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub(crate) struct GeneretoConfigRaw {
     #[serde(default)]
     pub template: String,
     /// Optional path to look for templates. Can be relative or absolute.
     #[serde(default)]
     pub template_base_path: Option<PathBuf>,
+    /// Where to write the generated website. Can be relative to the project folder or absolute.
+    #[serde(default = "output_dirname")]
+    pub output_dirname: PathBuf,
     /// title of the website - used in rss
     #[serde(default)]
     pub title: String,
@@ -71,6 +108,47 @@ pub(crate) struct GeneretoConfigRaw {
     // this is not an option because there is a default choice for each BlogConfig field
     // (in the Default trait impl)
     pub(crate) blog: GeneretoConfigBlogRaw,
+
+    #[serde(default)]
+    pub(crate) taxonomies: Vec<GeneretoConfigTaxonomyRaw>,
+
+    /// Feeds to generate for the blog: rss, atom and json. Defaults to rss only.
+    #[serde(default = "default_feeds")]
+    pub(crate) feeds: Vec<FeedFormat>,
+    /// Language of the website, used in feeds.
+    #[serde(default = "default_language")]
+    pub(crate) language: String,
+    /// Default author of the articles, used in feeds.
+    #[serde(default)]
+    pub(crate) author: Option<String>,
+    /// If true, feeds include the whole content of the articles instead of the description.
+    #[serde(default)]
+    pub(crate) feed_full_content: bool,
+    /// If true, writes sitemap.xml. Defaults to true.
+    #[serde(default = "default_sitemap")]
+    pub(crate) sitemap: bool,
+    /// Rules of robots.txt. The sitemap location is appended to them.
+    #[serde(default)]
+    pub(crate) robots_txt: Option<String>,
+    /// If true, writes search-index.json for client side search.
+    #[serde(default)]
+    pub(crate) search_index: bool,
+    /// Highlights code blocks at build time.
+    #[serde(default)]
+    pub(crate) highlight: Option<GeneretoConfigHighlight>,
+    /// Optional markdown extensions.
+    #[serde(default)]
+    pub(crate) markdown: GeneretoConfigMarkdown,
+    /// IANA name of the time zone of the dates without an offset, like "Europe/Rome".
+    #[serde(default)]
+    pub(crate) timezone: Option<String>,
+    /// Template of the pages past their expiry date, e.g. "expired.html".
+    #[serde(default)]
+    pub(crate) expired_template: Option<String>,
+    /// Theme folders providing the templates missing from the template folder, checked in
+    /// order. Can be relative to the project folder or absolute.
+    #[serde(default)]
+    pub(crate) themes: Vec<PathBuf>,
 }
 impl GeneretoConfigRaw {
     pub fn load_from_path(project_path: &Path) -> anyhow::Result<Self> {
@@ -111,6 +189,7 @@ mod tests {
         let expected_full_config = GeneretoConfigRaw {
             template: "test_template".into(),
             template_base_path: Some("a".into()),
+            output_dirname: "output".into(),
             title: "full_config".into(),
             url: "XXXXXXXXXXXXXXXX".into(),
             description: "Test description".into(),
@@ -124,11 +203,25 @@ mod tests {
                 default_cover_image: "Something.jpg".into(),
                 max_entries_per_page: None,
             },
+            taxonomies: vec![],
+            feeds: vec![FeedFormat::Rss],
+            language: "en-us".into(),
+            author: None,
+            feed_full_content: false,
+            sitemap: true,
+            robots_txt: None,
+            search_index: false,
+            highlight: None,
+            markdown: Default::default(),
+            timezone: None,
+            expired_template: None,
+            themes: vec![],
         };
 
         let expected_no_blog = GeneretoConfigRaw {
             template: "test_template".into(),
             template_base_path: None,
+            output_dirname: "output".into(),
             title: "no_blog".into(),
             url: "XXXXXXXXXXXXXXXX".into(),
             description: "Test description".into(),
@@ -142,6 +235,19 @@ mod tests {
                 default_cover_image: String::new(),
                 max_entries_per_page: None,
             },
+            taxonomies: vec![],
+            feeds: vec![FeedFormat::Rss],
+            language: "en-us".into(),
+            author: None,
+            feed_full_content: false,
+            sitemap: true,
+            robots_txt: None,
+            search_index: false,
+            highlight: None,
+            markdown: Default::default(),
+            timezone: None,
+            expired_template: None,
+            themes: vec![],
         };
 
         let no_blog = r#"
@@ -0,0 +1,93 @@
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+
+/// Formats of the dates without an offset, besides the day only "2024-01-15".
+const LOCAL_DATE_TIME_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+];
+
+/// Parses a date of the frontmatter: a day like "2024-01-15", a time of the day like
+/// "2024-01-15T10:30:00" or "2024-01-15 10:30", or an RFC 3339 date with its offset like
+/// "2024-01-15T10:30:00+02:00". Days start at midnight. Dates without an offset are in
+/// `timezone`, or in the local time zone if None.
+pub(crate) fn parse_date(value: &str, timezone: Option<Tz>) -> Option<DateTime<FixedOffset>> {
+    let value = value.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(date);
+    }
+    let local_date = LOCAL_DATE_TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()?
+                .and_hms_opt(0, 0, 0)
+        })?;
+    // Times skipped by a daylight saving change don't exist, they are not valid dates.
+    match timezone {
+        Some(timezone) => timezone
+            .from_local_datetime(&local_date)
+            .earliest()
+            .map(|date| date.fixed_offset()),
+        None => Local
+            .from_local_datetime(&local_date)
+            .earliest()
+            .map(|date| date.fixed_offset()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rfc3339(value: &str, timezone: Option<Tz>) -> Option<String> {
+        parse_date(value, timezone).map(|date| date.to_rfc3339())
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(
+            rfc3339("2024-01-15", Some(Tz::UTC)).as_deref(),
+            Some("2024-01-15T00:00:00+00:00")
+        );
+        assert_eq!(
+            rfc3339("2024-01-15T10:30:00", Some(Tz::UTC)).as_deref(),
+            Some("2024-01-15T10:30:00+00:00")
+        );
+        assert_eq!(
+            rfc3339("2024-01-15 10:30", Some(Tz::UTC)).as_deref(),
+            Some("2024-01-15T10:30:00+00:00")
+        );
+        // Without a timezone, dates are in the local one.
+        assert_eq!(
+            parse_date("2024-01-15 10:30", None),
+            Local
+                .with_ymd_and_hms(2024, 1, 15, 10, 30, 0)
+                .earliest()
+                .map(|date| date.fixed_offset())
+        );
+        assert_eq!(
+            rfc3339("2024-01-15T10:30:00+02:00", None).as_deref(),
+            Some("2024-01-15T10:30:00+02:00")
+        );
+        assert_eq!(
+            rfc3339("2024-01-15T10:30:00Z", Some(Tz::Europe__Rome)).as_deref(),
+            Some("2024-01-15T10:30:00+00:00")
+        );
+        assert_eq!(
+            rfc3339("2024-07-15 10:30", Some(Tz::Europe__Rome)).as_deref(),
+            Some("2024-07-15T10:30:00+02:00")
+        );
+        assert_eq!(
+            rfc3339("2024-01-15", Some(Tz::America__New_York)).as_deref(),
+            Some("2024-01-15T00:00:00-05:00")
+        );
+        // Clocks went from 02:00 to 03:00 in Rome.
+        assert_eq!(rfc3339("2024-03-31 02:30", Some(Tz::Europe__Rome)), None);
+        assert_eq!(rfc3339("", None), None);
+        assert_eq!(rfc3339("next monday", None), None);
+    }
+}
@@ -1,9 +1,17 @@
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::{fs, io};
 
-pub(crate) fn copy_directory_recursively(src: &Path, dest: &Path) -> io::Result<()> {
+/// Copies the file or folder `src` to `dest`, adding the path of every file in `dest` to
+/// `copied`.
+pub(crate) fn copy_directory_recursively(
+    src: &Path,
+    dest: &Path,
+    copied: &mut Vec<PathBuf>,
+) -> io::Result<()> {
     if src.is_file() {
-        fs::copy(src, dest)?;
+        copy_file_if_changed(src, dest)?;
+        copied.push(dest.to_path_buf());
     } else if src.is_dir() && src.file_name().unwrap() != ".git" {
         fs::create_dir_all(dest)?;
         let entries = fs::read_dir(src)?;
@@ -12,8 +20,126 @@ pub(crate) fn copy_directory_recursively(src: &Path, dest: &Path) -> io::Result<
             let entry_path = entry.path();
             let dest_path = dest.join(entry.file_name());
 
-            copy_directory_recursively(&entry_path, &dest_path)?;
+            copy_directory_recursively(&entry_path, &dest_path, copied)?;
+        }
+    }
+    Ok(())
+}
+
+/// Adds the files inside `dir` to `files`, keyed by their path inside `relative_dir`.
+/// Files already in `files` are kept, so earlier folders take precedence. Skips `.git`.
+pub(crate) fn collect_files(
+    dir: &Path,
+    relative_dir: &Path,
+    files: &mut BTreeMap<PathBuf, PathBuf>,
+) -> io::Result<()> {
+    for entry_path in sorted_dir_entries(dir)? {
+        let name = entry_path.file_name().unwrap();
+        if name == ".git" {
+            continue;
+        }
+        let relative_path = relative_dir.join(name);
+        if entry_path.is_dir() {
+            collect_files(&entry_path, &relative_path, files)?;
+        } else {
+            files.entry(relative_path).or_insert(entry_path);
+        }
+    }
+    Ok(())
+}
+
+/// Copies the file `src` to `dest`, unless `dest` is up to date. The copy gets the
+/// modification time of `src`, which tells it apart from a copy of another file.
+pub(crate) fn copy_file_if_changed(src: &Path, dest: &Path) -> io::Result<()> {
+    if !is_up_to_date(src, dest) {
+        fs::copy(src, dest)?;
+        let modified = fs::metadata(src)?.modified()?;
+        fs::File::options()
+            .write(true)
+            .open(dest)?
+            .set_modified(modified)?;
+    }
+    Ok(())
+}
+
+/// True if `dest` was copied from `src` and `src` didn't change since. Comparing the
+/// modification times for equality also catches `dest` being a copy of another file, like a
+/// theme asset newly overridden by an older file of the project.
+fn is_up_to_date(src: &Path, dest: &Path) -> bool {
+    let (Ok(src), Ok(dest)) = (fs::metadata(src), fs::metadata(dest)) else {
+        return false;
+    };
+    match (src.modified(), dest.modified()) {
+        (Ok(src_modified), Ok(dest_modified)) => {
+            src.len() == dest.len() && src_modified == dest_modified
+        }
+        _ => false,
+    }
+}
+
+/// Writes `content` to `path`, unless the file already has exactly that content.
+/// Keeps the modification time of unchanged outputs, so incremental builds don't touch them.
+pub(crate) fn write_if_changed<C: AsRef<[u8]>>(path: &Path, content: C) -> io::Result<()> {
+    let content = content.as_ref();
+    if fs::read(path).is_ok_and(|existing| existing == content) {
+        return Ok(());
+    }
+    fs::write(path, content)
+}
+
+/// Lists the entries of a directory sorted by path, so builds don't depend on the
+/// order returned by the filesystem.
+pub(crate) fn sorted_dir_entries(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+    Ok(entries)
+}
+
+/// Removes everything inside `dir`, except for a `.git` folder. The output folder can be
+/// a checkout of the deployment branch, which must survive a full rebuild.
+pub(crate) fn clear_directory(dir: &Path) -> io::Result<()> {
+    for entry_path in sorted_dir_entries(dir)? {
+        if entry_path.file_name().unwrap() == ".git" {
+            continue;
+        }
+        if entry_path.is_dir() {
+            fs::remove_dir_all(&entry_path)?;
+        } else {
+            fs::remove_file(&entry_path)?;
         }
     }
     Ok(())
 }
+
+/// MIME type of a file, guessed from its extension.
+pub(crate) fn mime_type(path: &Path) -> &'static str {
+    match path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_lowercase()
+        .as_str()
+    {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" => "text/javascript; charset=utf-8",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "txt" | "md" => "text/plain; charset=utf-8",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "avif" => "image/avif",
+        "mp3" => "audio/mpeg",
+        "mp4" => "video/mp4",
+        "pdf" => "application/pdf",
+        _ => "application/octet-stream",
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Date of the last commit of every file in a git repository.
+/// Loaded with a single `git log` walk, instead of spawning git once per page.
+#[derive(Debug, Default)]
+struct GitHistory {
+    /// Absolute file path -> last commit date, formatted as YYYY-MM-DD.
+    dates: HashMap<PathBuf, String>,
+}
+
+impl GitHistory {
+    /// On error, for example if git is not available in the system, it returns None.
+    fn load(repository_root: &Path) -> Option<Self> {
+        let output = Command::new("git")
+            .current_dir(repository_root)
+            .args(["-c", "core.quotepath=off", "log", "--name-only"])
+            .args(["--format=%x00%cd", "--date=short"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(Self::parse(
+            repository_root,
+            &String::from_utf8_lossy(&output.stdout),
+        ))
+    }
+
+    /// Parses the output of `git log --name-only --format=%x00%cd`: every commit is a NUL
+    /// prefixed date line, followed by the files it touched. Commits come newest first.
+    fn parse(repository_root: &Path, git_log: &str) -> Self {
+        let mut dates = HashMap::new();
+        let mut current_date = "";
+        for line in git_log.lines() {
+            if let Some(date) = line.strip_prefix('\0') {
+                current_date = date.trim();
+            } else if !line.is_empty() {
+                dates
+                    .entry(repository_root.join(line))
+                    .or_insert_with(|| current_date.to_string());
+            }
+        }
+        Self { dates }
+    }
+}
+
+#[derive(Default)]
+struct Cache {
+    /// Directory -> root of the repository containing it, if any.
+    roots: HashMap<PathBuf, Option<PathBuf>>,
+    histories: HashMap<PathBuf, Option<Arc<GitHistory>>>,
+}
+
+fn cache() -> &'static Mutex<Cache> {
+    static CACHE: OnceLock<Mutex<Cache>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+/// Forgets the loaded histories, so the next lookup sees the commits done in the meantime.
+/// Called at the start of every build.
+pub(crate) fn clear() {
+    *cache().lock().unwrap() = Cache::default();
+}
+
+/// Returns the date of the last commit touching `file_path`, formatted as YYYY-MM-DD.
+/// Returns None if the file is not committed, it is outside of a git repository or git
+/// is not available.
+pub(crate) fn last_modified_date(file_path: &Path) -> Option<String> {
+    let file_path = file_path.canonicalize().ok()?;
+    let directory = file_path.parent()?.to_path_buf();
+    let mut cache = cache().lock().unwrap();
+
+    let root = cache
+        .roots
+        .entry(directory)
+        .or_insert_with_key(|directory| repository_root(directory))
+        .clone()?;
+    let history = cache
+        .histories
+        .entry(root)
+        .or_insert_with_key(|root| {
+            debug!("Loading git history of {root:?}");
+            GitHistory::load(root).map(Arc::new)
+        })
+        .clone()?;
+    history.dates.get(&file_path).cloned()
+}
+
+fn repository_root(directory: &Path) -> Option<PathBuf> {
+    let output = Command::new("git")
+        .current_dir(directory)
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let root = String::from_utf8(output.stdout).ok()?;
+    PathBuf::from(root.trim()).canonicalize().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_git_log() {
+        let git_log = "\x002024-03-02\n\ncontent/blog/post.md\ncontent/index.md\n\n\x002024-01-01\n\ncontent/blog/post.md\ncontent/about.md\n";
+        let history = GitHistory::parse(Path::new("/repo"), git_log);
+        assert_eq!(history.dates.len(), 3);
+        assert_eq!(
+            history.dates[Path::new("/repo/content/blog/post.md")],
+            "2024-03-02"
+        );
+        assert_eq!(
+            history.dates[Path::new("/repo/content/index.md")],
+            "2024-03-02"
+        );
+        assert_eq!(
+            history.dates[Path::new("/repo/content/about.md")],
+            "2024-01-01"
+        );
+    }
+
+    #[test]
+    fn test_last_modified_date_outside_repository() {
+        let tmp_dir = tempfile::TempDir::with_prefix("example").unwrap();
+        let file_path = tmp_dir.path().join("page.md");
+        std::fs::write(&file_path, "content").unwrap();
+        assert_eq!(last_modified_date(&file_path), None);
+        // Files which don't exist have no date either.
+        assert_eq!(last_modified_date(&tmp_dir.path().join("missing.md")), None);
+    }
+}
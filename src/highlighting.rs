@@ -0,0 +1,388 @@
+use crate::build_cache::BuildCache;
+use crate::config::{GeneretoConfigHighlight, HighlightStyle};
+use crate::fs_util::write_if_changed;
+use crate::GeneretoConfig;
+use anyhow::{anyhow, Context};
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Tag, TagEnd};
+use std::collections::BTreeSet;
+use std::sync::OnceLock;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Color, Theme, ThemeSet};
+use syntect::html::{
+    css_for_theme_with_class_style, line_tokens_to_classed_spans, styled_line_to_highlighted_html,
+    ClassStyle, IncludeBackground,
+};
+use syntect::parsing::{ParseState, Scope, ScopeStack, SyntaxSet};
+
+const CSS_FILENAME: &str = "highlight.css";
+const CLASS_PREFIX: &str = "hl-";
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed {
+    prefix: CLASS_PREFIX,
+};
+/// Background of highlighted lines, for themes which don't define one.
+const DEFAULT_LINE_HIGHLIGHT: Color = Color {
+    r: 255,
+    g: 220,
+    b: 0,
+    a: 64,
+};
+const HIGHLIGHTED_LINE_CSS: &str = "display: inline-block; width: 100%";
+const LINE_NUMBER_CSS: &str =
+    "display: inline-block; min-width: 2em; padding-right: 1em; text-align: right; opacity: 0.6; user-select: none";
+
+/// Syntaxes and themes bundled with syntect. Loading them is slow, so it's done once.
+fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_nonewlines)
+}
+
+fn theme_set() -> &'static ThemeSet {
+    static THEME_SET: OnceLock<ThemeSet> = OnceLock::new();
+    THEME_SET.get_or_init(ThemeSet::load_defaults)
+}
+
+fn find_theme(name: &str) -> anyhow::Result<&'static Theme> {
+    theme_set().themes.get(name).ok_or_else(|| {
+        let available: Vec<&str> = theme_set().themes.keys().map(String::as_str).collect();
+        anyhow!(
+            "Unknown highlight theme '{name}'. Available themes: {}",
+            available.join(", ")
+        )
+    })
+}
+
+/// Checks the configured theme. With the classes style, writes its css to highlight.css in the
+/// output folder, for the templates to link.
+pub(crate) fn generate_theme_css(
+    genereto_config: &GeneretoConfig,
+    build_cache: &mut BuildCache,
+) -> anyhow::Result<()> {
+    let Some(highlight) = &genereto_config.highlight else {
+        return Ok(());
+    };
+    let theme = find_theme(&highlight.theme)?;
+    if highlight.style == HighlightStyle::Inline {
+        return Ok(());
+    }
+    let css_path = genereto_config.output_dir_path.join(CSS_FILENAME);
+    write_if_changed(&css_path, theme_css(theme)?)
+        .with_context(|| format!("Failed to write {css_path:?}"))?;
+    build_cache.record_output(&css_path);
+    Ok(())
+}
+
+fn theme_css(theme: &Theme) -> anyhow::Result<String> {
+    let mut css = css_for_theme_with_class_style(theme, CLASS_STYLE)?;
+    let line_highlight = theme
+        .settings
+        .line_highlight
+        .unwrap_or(DEFAULT_LINE_HIGHLIGHT);
+    css.push_str(&format!(
+        ".{CLASS_PREFIX}code .highlighted {{ {HIGHLIGHTED_LINE_CSS}; background-color: {}; }}\n",
+        css_color(line_highlight)
+    ));
+    css.push_str(&format!(
+        ".{CLASS_PREFIX}code .line-number {{ {LINE_NUMBER_CSS}; }}\n"
+    ));
+    Ok(css)
+}
+
+fn css_color(color: Color) -> String {
+    format!(
+        "#{:02x}{:02x}{:02x}{:02x}",
+        color.r, color.g, color.b, color.a
+    )
+}
+
+/// Replaces the fenced code blocks in `events` with their highlighted html.
+pub(crate) fn highlight_code_blocks<'a>(
+    events: impl Iterator<Item = Event<'a>>,
+    highlight: &GeneretoConfigHighlight,
+) -> Vec<Event<'a>> {
+    let mut result = vec![];
+    // Info string and text of the fenced code block being read.
+    let mut code_block: Option<(CowStr<'a>, String)> = None;
+    for event in events {
+        match (&mut code_block, event) {
+            (None, Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)))) => {
+                code_block = Some((info, String::new()));
+            }
+            (Some((_, code)), Event::Text(text)) => code.push_str(&text),
+            (Some(_), Event::End(TagEnd::CodeBlock)) => {
+                let (info, code) = code_block.take().unwrap();
+                match highlight_code(&code, &info, highlight) {
+                    Ok(html) => result.push(Event::Html(html.into())),
+                    Err(error) => {
+                        warn!("Failed to highlight a code block, leaving it as it is: {error:?}");
+                        result.push(Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))));
+                        result.push(Event::Text(code.into()));
+                        result.push(Event::End(TagEnd::CodeBlock));
+                    }
+                }
+            }
+            (_, event) => result.push(event),
+        }
+    }
+    result
+}
+
+/// Options of a code block, from the info string of its fence: "rust {3,5-7} linenos".
+#[derive(Debug, Default, PartialEq)]
+struct CodeBlockInfo {
+    language: String,
+    highlighted_lines: BTreeSet<usize>,
+    line_numbers: bool,
+}
+
+/// Highlighted lines past `line_count`, the lines of the code block, are left out.
+fn parse_info(info: &str, line_count: usize) -> CodeBlockInfo {
+    let mut block = CodeBlockInfo::default();
+    let mut rest = info.to_string();
+    if let (Some(start), Some(end)) = (info.find('{'), info.rfind('}')) {
+        if start < end {
+            block.highlighted_lines = parse_line_ranges(&info[start + 1..end], line_count);
+            rest = format!("{} {}", &info[..start], &info[end + 1..]);
+        }
+    }
+    for token in rest
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+    {
+        if token == "linenos" {
+            block.line_numbers = true;
+        } else if block.language.is_empty() {
+            block.language = token.to_string();
+        }
+    }
+    block
+}
+
+/// Parses line numbers and ranges like "3,5-7", up to line `line_count`.
+fn parse_line_ranges(ranges: &str, line_count: usize) -> BTreeSet<usize> {
+    let mut lines = BTreeSet::new();
+    for range in ranges.split(',').map(str::trim).filter(|r| !r.is_empty()) {
+        let parsed = match range.split_once('-') {
+            Some((start, end)) => start
+                .trim()
+                .parse()
+                .and_then(|start: usize| end.trim().parse().map(|end: usize| start..=end)),
+            None => range.parse().map(|line: usize| line..=line),
+        };
+        match parsed {
+            Ok(range) => lines.extend(*range.start()..=(*range.end()).min(line_count)),
+            Err(_) => warn!("Ignoring invalid line range '{range}' in a code block."),
+        }
+    }
+    lines
+}
+
+/// Highlights a code block. Languages are found by name or extension, like "rust" or "rs";
+/// unknown languages are rendered as plain text.
+fn highlight_code(
+    code: &str,
+    info: &str,
+    highlight: &GeneretoConfigHighlight,
+) -> anyhow::Result<String> {
+    let block = parse_info(info, code.lines().count());
+    let syntax_set = syntax_set();
+    let syntax = syntax_set
+        .find_syntax_by_token(&block.language)
+        .unwrap_or_else(|| {
+            if !block.language.is_empty() {
+                debug!("No syntax found for '{}'.", block.language);
+            }
+            syntax_set.find_syntax_plain_text()
+        });
+    let theme = find_theme(&highlight.theme)?;
+    let line_numbers = highlight.line_numbers || block.line_numbers;
+
+    let mut html = match highlight.style {
+        HighlightStyle::Classes => format!("<pre class=\"{CLASS_PREFIX}code\">"),
+        HighlightStyle::Inline => {
+            let background = theme.settings.background.unwrap_or(Color::WHITE);
+            format!(
+                "<pre style=\"background-color: {};\">",
+                css_color(background)
+            )
+        }
+    };
+    let is_safe_language = block
+        .language
+        .chars()
+        .all(|c| c.is_alphanumeric() || "+-#_.".contains(c));
+    if !block.language.is_empty() && is_safe_language {
+        html.push_str(&format!("<code class=\"language-{}\">", block.language));
+    } else {
+        html.push_str("<code>");
+    }
+
+    let mut renderer = match highlight.style {
+        HighlightStyle::Classes => LineRenderer::Classes {
+            parse_state: ParseState::new(syntax),
+            scope_stack: ScopeStack::new(),
+        },
+        HighlightStyle::Inline => LineRenderer::Inline(HighlightLines::new(syntax, theme)),
+    };
+    for (index, line) in code.lines().enumerate() {
+        let number = index + 1;
+        if !block.highlighted_lines.contains(&number) {
+            html.push_str("<span class=\"line\">");
+        } else if highlight.style == HighlightStyle::Inline {
+            let background = theme
+                .settings
+                .line_highlight
+                .unwrap_or(DEFAULT_LINE_HIGHLIGHT);
+            html.push_str(&format!(
+                "<span class=\"line highlighted\" style=\"{HIGHLIGHTED_LINE_CSS}; background-color: {};\">",
+                css_color(background)
+            ));
+        } else {
+            html.push_str("<span class=\"line highlighted\">");
+        }
+        if line_numbers {
+            match highlight.style {
+                HighlightStyle::Classes => html.push_str("<span class=\"line-number\">"),
+                HighlightStyle::Inline => html.push_str(&format!(
+                    "<span class=\"line-number\" style=\"{LINE_NUMBER_CSS};\">"
+                )),
+            }
+            html.push_str(&format!("{number}</span>"));
+        }
+        html.push_str(&renderer.render(line)?);
+        html.push_str("</span>\n");
+    }
+    html.push_str("</code></pre>\n");
+    Ok(html)
+}
+
+/// Highlights code line by line, keeping the parser state across lines.
+enum LineRenderer<'a> {
+    Classes {
+        parse_state: ParseState,
+        scope_stack: ScopeStack,
+    },
+    Inline(HighlightLines<'a>),
+}
+
+impl LineRenderer<'_> {
+    /// Returns the html of `line`. Spans never cross lines, so that every line can be wrapped.
+    fn render(&mut self, line: &str) -> anyhow::Result<String> {
+        match self {
+            LineRenderer::Classes {
+                parse_state,
+                scope_stack,
+            } => {
+                // Reopens the scopes left open by the previous lines.
+                let mut html: String = scope_stack
+                    .as_slice()
+                    .iter()
+                    .map(|scope| format!("<span class=\"{}\">", scope_classes(*scope)))
+                    .collect();
+                let ops = parse_state.parse_line(line, syntax_set())?;
+                let (spans, _) =
+                    line_tokens_to_classed_spans(line, &ops, CLASS_STYLE, scope_stack)?;
+                html.push_str(&spans);
+                html.push_str(&"</span>".repeat(scope_stack.len()));
+                Ok(html)
+            }
+            LineRenderer::Inline(highlighter) => {
+                let regions = highlighter.highlight_line(line, syntax_set())?;
+                Ok(styled_line_to_highlighted_html(
+                    &regions,
+                    IncludeBackground::No,
+                )?)
+            }
+        }
+    }
+}
+
+/// Same classes syntect gives to the spans of `scope`: "hl-source hl-rust".
+fn scope_classes(scope: Scope) -> String {
+    scope
+        .build_string()
+        .split('.')
+        .map(|atom| format!("{CLASS_PREFIX}{atom}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(style: HighlightStyle, line_numbers: bool) -> GeneretoConfigHighlight {
+        GeneretoConfigHighlight {
+            theme: "InspiredGitHub".to_string(),
+            style,
+            line_numbers,
+        }
+    }
+
+    #[test]
+    fn test_parse_info() {
+        assert_eq!(
+            parse_info("rust {3,5-7} linenos", 10),
+            CodeBlockInfo {
+                language: "rust".to_string(),
+                highlighted_lines: [3, 5, 6, 7].into(),
+                line_numbers: true,
+            }
+        );
+        assert_eq!(
+            parse_info("python{1}", 1),
+            CodeBlockInfo {
+                language: "python".to_string(),
+                highlighted_lines: [1].into(),
+                line_numbers: false,
+            }
+        );
+        assert_eq!(parse_info("", 0), CodeBlockInfo::default());
+        assert_eq!(parse_line_ranges("2, x, 4-a, 9", 10), [2, 9].into());
+        // Ranges stop at the last line of the code block.
+        assert_eq!(parse_line_ranges("2-4000000000, 12", 3), [2, 3].into());
+    }
+
+    #[test]
+    fn test_highlight_code_with_classes() -> anyhow::Result<()> {
+        let code = "/* a comment\nspanning lines */\nfn main() {}\n";
+        let html = highlight_code(code, "rust {2}", &config(HighlightStyle::Classes, true))?;
+        assert!(html.starts_with("<pre class=\"hl-code\"><code class=\"language-rust\">"));
+        assert!(
+            html.contains("<span class=\"line highlighted\"><span class=\"line-number\">2</span>")
+        );
+        assert!(html.contains("<span class=\"hl-storage hl-type hl-function hl-rust\">fn</span>"));
+        // The comment is closed at the end of the first line and reopened on the second one.
+        let lines: Vec<&str> = html.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[3], "</code></pre>");
+        for line in &lines {
+            assert_eq!(
+                line.matches("<span").count(),
+                line.matches("</span>").count()
+            );
+        }
+        assert!(lines[1].contains("hl-comment"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_highlight_code_inline() -> anyhow::Result<()> {
+        let html = highlight_code(
+            "let x = 1;\n",
+            "unknown",
+            &config(HighlightStyle::Inline, false),
+        )?;
+        assert!(html.starts_with(
+            "<pre style=\"background-color: #ffffffff;\"><code class=\"language-unknown\">"
+        ));
+        assert!(html.contains("<span style=\"color:"));
+        assert!(!html.contains("line-number"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_theme() {
+        let error = find_theme("missing").unwrap_err().to_string();
+        assert!(error.contains("InspiredGitHub"));
+    }
+}
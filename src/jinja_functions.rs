@@ -0,0 +1,197 @@
+use crate::build_cache;
+use crate::dates::parse_date;
+use crate::page_metadata::estimate_reading_time;
+use crate::parser::{
+    compile_markdown_to_html, find_template, get_anchor_id_from_title, MarkdownOptions,
+};
+use chrono::format::{Item, StrftimeItems};
+use chrono_tz::Tz;
+use minijinja::{Environment, Error, ErrorKind, Value};
+use std::fs;
+use std::path::PathBuf;
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Adds the genereto filters and functions to `env`:
+/// - `date(format)`: formats a date like `publish_date` with a chrono format string. Dates
+///   without an offset are in the timezone of the site.
+/// - `slugify`: the slug used for anchors and taxonomy terms.
+/// - `markdown`: renders a markdown string to html.
+/// - `absolute_url` and `relative_url`: url of a path of the website, with or without the
+///   domain.
+/// - `reading_time`: estimated reading time of a text, in minutes.
+/// - `truncate_words(count)`: first words of a text.
+/// - `json`: serializes a value, e.g. for JSON-LD.
+/// - `asset(path)`: absolute url of a file of the template folders, fingerprinted with its hash
+///   so browsers don't use a stale copy.
+pub(crate) fn register(
+    env: &mut Environment<'static>,
+    site_url: &str,
+    template_dirs: Vec<PathBuf>,
+    markdown_options: MarkdownOptions,
+) {
+    let site_url = site_url.trim_end_matches('/').to_string();
+    let base_path = base_path(&site_url);
+
+    let timezone = markdown_options.timezone;
+    env.add_filter("date", move |value: String, format: Option<String>| {
+        format_date(&value, format.as_deref(), timezone)
+    });
+    env.add_filter("slugify", |value: String| get_anchor_id_from_title(&value));
+    env.add_filter("markdown", move |value: String| {
+        compile_markdown_to_html(&value, &markdown_options)
+    });
+    let url = site_url.clone();
+    env.add_filter("absolute_url", move |path: String| {
+        absolute_url(&url, &path)
+    });
+    env.add_filter("relative_url", move |path: String| {
+        absolute_url(&base_path, &path)
+    });
+    env.add_filter("reading_time", |value: String| {
+        estimate_reading_time(&value)
+    });
+    env.add_filter("truncate_words", truncate_words);
+    env.add_filter("json", |value: Value| {
+        serde_json::to_string(&value).map_err(|error| {
+            Error::new(ErrorKind::InvalidOperation, "Failed to serialize to JSON")
+                .with_source(error)
+        })
+    });
+    env.add_function("asset", move |path: String| {
+        let asset_path = find_template(&template_dirs, path.trim_start_matches('/'));
+        let content = fs::read(&asset_path).map_err(|error| {
+            Error::new(
+                ErrorKind::InvalidOperation,
+                format!("Failed to read the asset {asset_path:?}"),
+            )
+            .with_source(error)
+        })?;
+        let fingerprint = &build_cache::hash(&content)[..8];
+        Ok::<_, Error>(format!(
+            "{}?v={fingerprint}",
+            absolute_url(&site_url, &path)
+        ))
+    });
+}
+
+/// Path of the website on its domain, e.g. `/blog` for `https://example.com/blog`.
+fn base_path(site_url: &str) -> String {
+    let without_scheme = site_url
+        .split_once("://")
+        .map_or(site_url, |(_, rest)| rest);
+    match without_scheme.find('/') {
+        Some(start) => without_scheme[start..].to_string(),
+        None => String::new(),
+    }
+}
+
+fn absolute_url(base: &str, path: &str) -> String {
+    format!("{base}/{}", path.trim_start_matches('/'))
+}
+
+fn format_date(value: &str, format: Option<&str>, timezone: Option<Tz>) -> Result<String, Error> {
+    let format = format.unwrap_or(DEFAULT_DATE_FORMAT);
+    let date = parse_date(value, timezone).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidOperation,
+            format!("'{value}' is not a date like 2024-01-05 or 2024-01-05T10:00:00Z"),
+        )
+    })?;
+    let items: Vec<Item> = StrftimeItems::new(format).collect();
+    if items.contains(&Item::Error) {
+        return Err(Error::new(
+            ErrorKind::InvalidOperation,
+            format!("'{format}' is not a valid date format"),
+        ));
+    }
+    Ok(date.format_with_items(items.into_iter()).to_string())
+}
+
+fn truncate_words(value: String, count: usize) -> String {
+    let words: Vec<&str> = value.split_whitespace().collect();
+    if words.len() <= count {
+        return words.join(" ");
+    }
+    format!("{}...", words[..count].join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str) -> Result<String, Error> {
+        let mut env = Environment::new();
+        register(
+            &mut env,
+            "https://example.com/blog/",
+            vec![PathBuf::from("sample-genereto-project/templates/main")],
+            MarkdownOptions {
+                timezone: Some(Tz::UTC),
+                ..Default::default()
+            },
+        );
+        env.render_str(template, minijinja::context! {})
+    }
+
+    #[test]
+    fn test_filters() -> Result<(), Error> {
+        assert_eq!(
+            render(r#"{{ "2024-01-05" | date("%b %-d, %Y") }}"#)?,
+            "Jan 5, 2024"
+        );
+        assert_eq!(
+            render(r#"{{ "2024-01-05T10:30:00+02:00" | date("%H:%M") }}"#)?,
+            "10:30"
+        );
+        assert_eq!(
+            render(r#"{{ "2024-01-05 10:30" | date("%H:%M %z") }}"#)?,
+            "10:30 +0000"
+        );
+        assert!(render(r#"{{ "yesterday" | date }}"#).is_err());
+        assert!(render(r#"{{ "2024-01-05" | date("%Q") }}"#).is_err());
+        assert_eq!(render(r#"{{ "Hello, World!" | slugify }}"#)?, "hello-world");
+        assert_eq!(
+            render(r#"{{ "Some *text*" | markdown }}"#)?,
+            "<p>Some <em>text</em></p>\n"
+        );
+        assert_eq!(
+            render(r#"{{ "/tags/rust.html" | absolute_url }}"#)?,
+            "https://example.com/blog/tags/rust.html"
+        );
+        assert_eq!(
+            render(r#"{{ "tags/rust.html" | relative_url }}"#)?,
+            "/blog/tags/rust.html"
+        );
+        assert_eq!(render(r#"{{ "a few words" | reading_time }}"#)?, "1");
+        assert_eq!(
+            render(r#"{{ "one two  three" | truncate_words(2) }}"#)?,
+            "one two..."
+        );
+        assert_eq!(render(r#"{{ "one two" | truncate_words(2) }}"#)?, "one two");
+        assert_eq!(
+            render(r#"{{ {"title": "A \"quote\""} | json }}"#)?,
+            r#"{"title":"A \"quote\""}"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_asset() -> Result<(), Error> {
+        let url = render(r#"{{ asset("/res/styles.css") }}"#)?;
+        assert!(url.starts_with("https://example.com/blog/res/styles.css?v="));
+        assert_eq!(
+            url.len(),
+            "https://example.com/blog/res/styles.css?v=".len() + 8
+        );
+        assert!(render(r#"{{ asset("missing.css") }}"#).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_base_path() {
+        assert_eq!(base_path("https://example.com"), "");
+        assert_eq!(base_path("https://example.com/blog"), "/blog");
+        assert_eq!(base_path("localhost:8080/docs"), "/docs");
+    }
+}
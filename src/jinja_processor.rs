@@ -1,7 +1,13 @@
+use crate::jinja_functions;
+use crate::page_metadata::PageMetadata;
+use crate::parser::MarkdownOptions;
+use crate::GeneretoConfig;
+use anyhow::Context;
 use chrono::Datelike;
-use minijinja::{context, Environment};
+use minijinja::{context, AutoEscape, Environment};
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 
 /// Site-level context available in all templates
 #[derive(Debug, Clone, Serialize)]
@@ -10,6 +16,12 @@ pub struct SiteContext {
     pub url: String,
     pub description: String,
     pub current_year: i32,
+    /// Pages outside of the blog, sorted by path.
+    pub pages: Vec<PageContext>,
+    /// Blog articles, newest first like in the blog index.
+    pub articles: Vec<PageContext>,
+    /// Taxonomies declared in the config, by name.
+    pub taxonomies: BTreeMap<String, TaxonomyContext>,
 }
 
 impl SiteContext {
@@ -19,6 +31,9 @@ impl SiteContext {
             url: url.to_string(),
             description: description.to_string(),
             current_year: chrono::Local::now().year(),
+            pages: vec![],
+            articles: vec![],
+            taxonomies: BTreeMap::new(),
         }
     }
 }
@@ -27,55 +42,65 @@ impl SiteContext {
 #[derive(Debug, Clone, Serialize)]
 pub struct PageContext {
     pub title: String,
+    /// Publish date as written in the frontmatter.
     pub publish_date: String,
+    /// Publish date in the RFC 3339 format, with the time and the offset. Empty if the page
+    /// has no valid publish date.
+    pub publish_datetime: String,
+    /// Expiry date as written in the frontmatter, empty if the page doesn't expire.
+    pub expiry_date: String,
     pub description: String,
     pub keywords: String,
     pub file_name: String,
     pub page_name: String,
+    /// Path of the page relative to the output folder.
+    pub path: String,
     pub cover_image: String,
     pub table_of_contents: String,
     pub read_time_minutes: String,
     pub last_modified_date: String,
     pub url: String,
+    /// Previous article, the one published before this one. Only set for articles.
+    pub prev: Option<Box<PageContext>>,
+    /// Next article, the one published after this one. Only set for articles.
+    pub next: Option<Box<PageContext>>,
+    /// Articles and pages sharing taxonomy terms or keywords with this page, most related first.
+    pub related: Vec<PageContext>,
     #[serde(flatten)]
-    pub custom_metadata: HashMap<String, String>,
+    pub custom_metadata: HashMap<String, serde_json::Value>,
 }
 
 impl PageContext {
-    pub fn from_page_metadata(metadata: &crate::page_metadata::PageMetadata) -> Self {
+    pub fn from_page_metadata(metadata: &PageMetadata) -> Self {
         Self {
             title: metadata.title.clone(),
             publish_date: metadata.publish_date.clone(),
+            publish_datetime: metadata.publish_datetime(),
+            expiry_date: metadata.expiry_date.clone(),
             description: metadata.description.clone(),
             keywords: metadata.keywords.clone(),
             file_name: metadata.file_name.clone(),
             page_name: metadata.page_name.clone(),
+            path: metadata.path.clone(),
             cover_image: metadata.cover_image.clone(),
             table_of_contents: metadata.table_of_contents.clone(),
             read_time_minutes: metadata.reading_time_mins.clone(),
             last_modified_date: metadata.last_modified_date.clone(),
             url: metadata.article_url.clone().unwrap_or_default(),
+            prev: None,
+            next: None,
+            related: vec![],
             custom_metadata: metadata.custom_metadata.clone(),
         }
     }
 }
 
-/// Render a page template with Jinja2
-pub fn render_page(
-    template: &str,
-    site: &SiteContext,
-    page: &PageContext,
-    content: &str,
-) -> anyhow::Result<String> {
-    let mut env = Environment::new();
-    env.add_template("page", template)?;
-    let tmpl = env.get_template("page")?;
-    let result = tmpl.render(context! {
-        site => site,
-        page => page,
-        content => content,
-    })?;
-    Ok(result)
+/// Links of a page to the other pages of the website, see `PageContext`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PageLinks {
+    pub(crate) prev: Option<PageContext>,
+    pub(crate) next: Option<PageContext>,
+    pub(crate) related: Vec<PageContext>,
 }
 
 /// Pagination context available in blog index templates
@@ -89,22 +114,218 @@ pub struct PaginationContext {
     pub next_url: String,
 }
 
+/// A term of a taxonomy, e.g. the "rust" tag
+#[derive(Debug, Clone, Serialize)]
+pub struct TermContext {
+    pub name: String,
+    pub slug: String,
+    /// Number of pages using this term
+    pub count: usize,
+    /// Path of the term page relative to the output folder
+    pub path: String,
+    /// Absolute url of the term page
+    pub url: String,
+    /// Absolute url of the RSS feed of the term
+    pub feed_url: String,
+}
+
+/// A taxonomy with all of its terms, e.g. the tags used across the website
+#[derive(Debug, Clone, Serialize)]
+pub struct TaxonomyContext {
+    pub name: String,
+    pub terms: Vec<TermContext>,
+}
+
+/// Jinja2 environment shared by all the templates of a build, with the site context.
+/// Templates are loaded from the template folder, so they can extend, include and import
+/// each other.
+#[derive(Debug)]
+pub struct JinjaEnvironment {
+    env: Environment<'static>,
+    pub site: SiteContext,
+    /// Links of the pages to each other, by source file.
+    links: HashMap<PathBuf, PageLinks>,
+}
+
+impl JinjaEnvironment {
+    /// `markdown_options` are used by the `markdown` filter.
+    /// Templates are looked up in `template_dirs` in order, so the first folder overrides
+    /// the following ones.
+    pub fn new(
+        template_dirs: &[PathBuf],
+        site: SiteContext,
+        markdown_options: MarkdownOptions,
+    ) -> Self {
+        let mut env = Environment::new();
+        let loaders: Vec<_> = template_dirs.iter().map(minijinja::path_loader).collect();
+        env.set_loader(move |name| {
+            for loader in &loaders {
+                if let Some(template) = loader(name)? {
+                    return Ok(Some(template));
+                }
+            }
+            Ok(None)
+        });
+        // Like the markers, variables are written as they are: the content is already html.
+        env.set_auto_escape_callback(|_| AutoEscape::None);
+        jinja_functions::register(
+            &mut env,
+            &site.url,
+            template_dirs.to_vec(),
+            markdown_options,
+        );
+        Self {
+            env,
+            site,
+            links: HashMap::new(),
+        }
+    }
+
+    /// The environment of a build, if Jinja2 is enabled in the config.
+    pub fn from_config(genereto_config: &GeneretoConfig) -> Option<Self> {
+        genereto_config.enable_jinja.then(|| {
+            Self::new(
+                &genereto_config.template_dirs,
+                SiteContext::new(
+                    &genereto_config.title,
+                    &genereto_config.url,
+                    &genereto_config.description,
+                ),
+                MarkdownOptions::new(genereto_config),
+            )
+        })
+    }
+
+    pub(crate) fn set_links(&mut self, links: HashMap<PathBuf, PageLinks>) {
+        self.links = links;
+    }
+
+    /// Context of the page compiled from `source`, with its links to the other pages.
+    pub fn page_context(&self, metadata: &PageMetadata, source: &Path) -> PageContext {
+        let mut page = PageContext::from_page_metadata(metadata);
+        if let Some(links) = self.links.get(source) {
+            page.prev = links.prev.clone().map(Box::new);
+            page.next = links.next.clone().map(Box::new);
+            page.related = links.related.clone();
+        }
+        page
+    }
+
+    /// Renders `template`, read from the file `name` of the template folder. The name shows up
+    /// in the errors, and in the ones of the templates it extends or includes.
+    fn render<S: Serialize>(&self, name: &str, template: &str, ctx: S) -> anyhow::Result<String> {
+        self.env
+            .render_named_str(name, template, ctx)
+            .with_context(|| format!("Failed to render the template {name}"))
+    }
+
+    /// Render a page template with Jinja2
+    pub fn render_page(
+        &self,
+        name: &str,
+        template: &str,
+        page: &PageContext,
+        content: &str,
+    ) -> anyhow::Result<String> {
+        self.render(
+            name,
+            template,
+            context! {
+                site => &self.site,
+                page => page,
+                content => content,
+            },
+        )
+    }
+
+    /// Render blog index template with articles list
+    pub fn render_blog_index(
+        &self,
+        name: &str,
+        template: &str,
+        articles: &[PageContext],
+        pagination: Option<&PaginationContext>,
+    ) -> anyhow::Result<String> {
+        self.render(
+            name,
+            template,
+            context! {
+                site => &self.site,
+                articles => articles,
+                pagination => pagination,
+            },
+        )
+    }
+
+    /// Render a taxonomy page. Term pages get the `term` and its `articles`, while the term
+    /// index only gets the `taxonomy` with its terms.
+    pub fn render_taxonomy_page(
+        &self,
+        name: &str,
+        template: &str,
+        taxonomy: &TaxonomyContext,
+        term: Option<&TermContext>,
+        articles: &[PageContext],
+        pagination: Option<&PaginationContext>,
+    ) -> anyhow::Result<String> {
+        self.render(
+            name,
+            template,
+            context! {
+                site => &self.site,
+                taxonomy => taxonomy,
+                term => term,
+                articles => articles,
+                pagination => pagination,
+            },
+        )
+    }
+
+    /// Render a shortcode template. The arguments of the shortcode are top level variables,
+    /// next to `site`, `page` and the `body` between the opening and the closing tags.
+    pub fn render_shortcode(
+        &self,
+        name: &str,
+        template: &str,
+        page: &PageContext,
+        arguments: &HashMap<String, String>,
+        body: &str,
+    ) -> anyhow::Result<String> {
+        self.render(
+            name,
+            template,
+            context! {
+                site => &self.site,
+                page => page,
+                body => body,
+                ..minijinja::Value::from_serialize(arguments)
+            },
+        )
+    }
+}
+
+/// Render a page template with Jinja2
+#[deprecated(note = "Use `JinjaEnvironment::render_page`, which can load other templates")]
+pub fn render_page(
+    template: &str,
+    site: &SiteContext,
+    page: &PageContext,
+    content: &str,
+) -> anyhow::Result<String> {
+    JinjaEnvironment::new(&[], site.clone(), MarkdownOptions::default())
+        .render_page("page", template, page, content)
+}
+
 /// Render blog index template with articles list
+#[deprecated(note = "Use `JinjaEnvironment::render_blog_index`, which can load other templates")]
 pub fn render_blog_index(
     template: &str,
     site: &SiteContext,
     articles: &[PageContext],
     pagination: Option<&PaginationContext>,
 ) -> anyhow::Result<String> {
-    let mut env = Environment::new();
-    env.add_template("index", template)?;
-    let tmpl = env.get_template("index")?;
-    let result = tmpl.render(context! {
-        site => site,
-        articles => articles,
-        pagination => pagination,
-    })?;
-    Ok(result)
+    JinjaEnvironment::new(&[], site.clone(), MarkdownOptions::default())
+        .render_blog_index("index", template, articles, pagination)
 }
 
 #[cfg(test)]
@@ -115,50 +336,89 @@ mod tests {
         SiteContext::new("Test Site", "https://example.com", "A test site")
     }
 
+    fn create_test_environment() -> JinjaEnvironment {
+        JinjaEnvironment::new(
+            &[PathBuf::from("templates")],
+            create_test_site_context(),
+            MarkdownOptions::default(),
+        )
+    }
+
     fn create_test_page_context() -> PageContext {
         PageContext {
             title: "Test Page".to_string(),
             publish_date: "2024-01-15".to_string(),
+            publish_datetime: "2024-01-15T00:00:00+00:00".to_string(),
+            expiry_date: String::new(),
             description: "A test page description".to_string(),
             keywords: "test, page".to_string(),
             file_name: "test-page.html".to_string(),
             page_name: "test-page".to_string(),
+            path: "test-page.html".to_string(),
             cover_image: "cover.jpg".to_string(),
             table_of_contents: "<ul><li>Section 1</li></ul>".to_string(),
             read_time_minutes: "5".to_string(),
             last_modified_date: "2024-01-20".to_string(),
             url: "".to_string(),
+            prev: None,
+            next: None,
+            related: vec![],
             custom_metadata: HashMap::new(),
         }
     }
 
+    #[test]
+    #[allow(deprecated)]
+    fn test_deprecated_render_functions() {
+        let site = create_test_site_context();
+        let page = create_test_page_context();
+        let result = render_page(
+            "{{ site.title }}: {{ page.title }} {{ content }}",
+            &site,
+            &page,
+            "<p>Hello</p>",
+        )
+        .unwrap();
+        assert_eq!(result, "Test Site: Test Page <p>Hello</p>");
+        let result = render_blog_index(
+            "{% for article in articles %}{{ article.title }}{% endfor %}",
+            &site,
+            &[page],
+            None,
+        )
+        .unwrap();
+        assert_eq!(result, "Test Page");
+    }
+
     #[test]
     fn test_render_page_basic() {
         let template = "<html><title>{{ page.title }}</title></html>";
-        let site = create_test_site_context();
+        let jinja = create_test_environment();
         let page = create_test_page_context();
 
-        let result = render_page(template, &site, &page, "").unwrap();
+        let result = jinja.render_page("page.html", template, &page, "").unwrap();
         assert_eq!(result, "<html><title>Test Page</title></html>");
     }
 
     #[test]
     fn test_render_page_with_site_context() {
         let template = "<html><title>{{ page.title }} - {{ site.title }}</title></html>";
-        let site = create_test_site_context();
+        let jinja = create_test_environment();
         let page = create_test_page_context();
 
-        let result = render_page(template, &site, &page, "").unwrap();
+        let result = jinja.render_page("page.html", template, &page, "").unwrap();
         assert_eq!(result, "<html><title>Test Page - Test Site</title></html>");
     }
 
     #[test]
     fn test_render_page_with_content() {
         let template = "<html><body>{{ content }}</body></html>";
-        let site = create_test_site_context();
+        let jinja = create_test_environment();
         let page = create_test_page_context();
 
-        let result = render_page(template, &site, &page, "<p>Hello World</p>").unwrap();
+        let result = jinja
+            .render_page("page.html", template, &page, "<p>Hello World</p>")
+            .unwrap();
         assert_eq!(result, "<html><body><p>Hello World</p></body></html>");
     }
 
@@ -181,11 +441,13 @@ mod tests {
 </body>
 </html>"#;
 
-        let site = create_test_site_context();
+        let jinja = create_test_environment();
         let page = create_test_page_context();
         let content = "<p>Article content here</p>";
 
-        let result = render_page(template, &site, &page, content).unwrap();
+        let result = jinja
+            .render_page("page.html", template, &page, content)
+            .unwrap();
 
         assert!(result.contains("<title>Test Page</title>"));
         assert!(result.contains("content=\"A test page description\""));
@@ -203,14 +465,14 @@ mod tests {
     fn test_render_page_with_custom_metadata() {
         let template =
             "<html><p>Author: {{ page.author }}</p><p>Category: {{ page.category }}</p></html>";
-        let site = create_test_site_context();
+        let jinja = create_test_environment();
         let mut page = create_test_page_context();
         page.custom_metadata
-            .insert("author".to_string(), "John Doe".to_string());
+            .insert("author".to_string(), "John Doe".into());
         page.custom_metadata
-            .insert("category".to_string(), "Tech".to_string());
+            .insert("category".to_string(), "Tech".into());
 
-        let result = render_page(template, &site, &page, "").unwrap();
+        let result = jinja.render_page("page.html", template, &page, "").unwrap();
         assert!(result.contains("Author: John Doe"));
         assert!(result.contains("Category: Tech"));
     }
@@ -230,39 +492,53 @@ mod tests {
 </body>
 </html>"#;
 
-        let site = create_test_site_context();
+        let jinja = create_test_environment();
         let articles = vec![
             PageContext {
                 title: "First Post".to_string(),
                 publish_date: "2024-01-15".to_string(),
+                publish_datetime: "2024-01-15T00:00:00+00:00".to_string(),
+                expiry_date: String::new(),
                 description: "First post description".to_string(),
                 keywords: "".to_string(),
                 file_name: "first-post.html".to_string(),
                 page_name: "first-post".to_string(),
+                path: "first-post.html".to_string(),
                 cover_image: "".to_string(),
                 table_of_contents: "".to_string(),
                 read_time_minutes: "3".to_string(),
                 last_modified_date: "".to_string(),
                 url: "".to_string(),
+                prev: None,
+                next: None,
+                related: vec![],
                 custom_metadata: HashMap::new(),
             },
             PageContext {
                 title: "Second Post".to_string(),
                 publish_date: "2024-01-20".to_string(),
+                publish_datetime: "2024-01-20T00:00:00+00:00".to_string(),
+                expiry_date: String::new(),
                 description: "Second post description".to_string(),
                 keywords: "".to_string(),
                 file_name: "second-post.html".to_string(),
                 page_name: "second-post".to_string(),
+                path: "second-post.html".to_string(),
                 cover_image: "".to_string(),
                 table_of_contents: "".to_string(),
                 read_time_minutes: "5".to_string(),
                 last_modified_date: "".to_string(),
                 url: "".to_string(),
+                prev: None,
+                next: None,
+                related: vec![],
                 custom_metadata: HashMap::new(),
             },
         ];
 
-        let result = render_blog_index(template, &site, &articles, None).unwrap();
+        let result = jinja
+            .render_blog_index("index.html", template, &articles, None)
+            .unwrap();
 
         assert!(result.contains("<h1>Test Site</h1>"));
         assert!(result.contains("<a href=\"first-post.html\">First Post</a>"));
@@ -286,13 +562,56 @@ mod tests {
 </body>
 </html>"#;
 
-        let site = create_test_site_context();
+        let jinja = create_test_environment();
         let articles: Vec<PageContext> = vec![];
 
-        let result = render_blog_index(template, &site, &articles, None).unwrap();
+        let result = jinja
+            .render_blog_index("index.html", template, &articles, None)
+            .unwrap();
         assert!(result.contains("No articles yet."));
     }
 
+    #[test]
+    fn test_render_taxonomy_page() {
+        let template = r#"{% if term %}<h1>{{ taxonomy.name }}: {{ term.name }}</h1>{% for article in articles %}<a href="{{ article.file_name }}">{{ article.title }}</a>{% endfor %}{% else %}{% for term in taxonomy.terms %}<a href="{{ term.url }}">{{ term.name }} ({{ term.count }})</a>{% endfor %}{% endif %}"#;
+        let jinja = create_test_environment();
+        let term = TermContext {
+            name: "Rust".to_string(),
+            slug: "rust".to_string(),
+            count: 1,
+            path: "tags/rust/index.html".to_string(),
+            url: "https://example.com/tags/rust/index.html".to_string(),
+            feed_url: "https://example.com/tags/rust/rss.xml".to_string(),
+        };
+        let taxonomy = TaxonomyContext {
+            name: "tags".to_string(),
+            terms: vec![term.clone()],
+        };
+
+        let result = jinja
+            .render_taxonomy_page(
+                "tag.html",
+                template,
+                &taxonomy,
+                Some(&term),
+                &[create_test_page_context()],
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            result,
+            "<h1>tags: Rust</h1><a href=\"test-page.html\">Test Page</a>"
+        );
+
+        let result = jinja
+            .render_taxonomy_page("tag.html", template, &taxonomy, None, &[], None)
+            .unwrap();
+        assert_eq!(
+            result,
+            "<a href=\"https://example.com/tags/rust/index.html\">Rust (1)</a>"
+        );
+    }
+
     #[test]
     fn test_site_context_current_year() {
         let site = SiteContext::new("Test", "https://test.com", "Test");
@@ -308,16 +627,18 @@ mod tests {
 {% endif %}
 </html>"#;
 
-        let site = create_test_site_context();
+        let jinja = create_test_environment();
         let page = create_test_page_context();
 
-        let result = render_page(template, &site, &page, "").unwrap();
+        let result = jinja.render_page("page.html", template, &page, "").unwrap();
         assert!(result.contains("src=\"cover.jpg\""));
 
         // Test with empty cover image
         let mut page_no_cover = create_test_page_context();
         page_no_cover.cover_image = "".to_string();
-        let result = render_page(template, &site, &page_no_cover, "").unwrap();
+        let result = jinja
+            .render_page("page.html", template, &page_no_cover, "")
+            .unwrap();
         assert!(!result.contains("<img"));
     }
 }
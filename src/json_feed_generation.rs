@@ -0,0 +1,156 @@
+use crate::fs_util::write_if_changed;
+use crate::page_metadata::PageMetadata;
+use crate::rss_generation::{
+    article_author, article_content, article_link, cover_image_file, is_feed_article, keywords,
+    FeedInfo,
+};
+use anyhow::Result;
+use serde::Serialize;
+use std::path::Path;
+
+const JSON_FEED_VERSION: &str = "https://jsonfeed.org/version/1.1";
+
+/// JSON Feed 1.1, see https://www.jsonfeed.org/version/1.1/
+#[derive(Debug, Serialize)]
+struct JsonFeed {
+    version: &'static str,
+    title: String,
+    home_page_url: String,
+    feed_url: String,
+    description: String,
+    language: String,
+    items: Vec<JsonFeedItem>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonFeedItem {
+    id: String,
+    url: String,
+    title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_html: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_text: Option<String>,
+    summary: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    authors: Vec<JsonFeedAuthor>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date_published: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date_modified: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonFeedAuthor {
+    name: String,
+}
+
+/// Writes a JSON Feed of the articles to `output_path`.
+pub fn generate_json_feed(
+    feed: &FeedInfo,
+    metadatas: Vec<PageMetadata>,
+    output_path: &Path,
+) -> Result<()> {
+    let file_name = output_path.file_name().unwrap().to_string_lossy();
+    let json_feed = JsonFeed {
+        version: JSON_FEED_VERSION,
+        title: feed.title.clone(),
+        home_page_url: feed.url.to_string(),
+        feed_url: format!("{}/{file_name}", feed.url),
+        description: feed.description.to_string(),
+        language: feed.language.to_string(),
+        items: articles_to_items(feed, metadatas),
+    };
+    write_if_changed(output_path, serde_json::to_string_pretty(&json_feed)?)?;
+    Ok(())
+}
+
+fn articles_to_items(feed: &FeedInfo, metadatas: Vec<PageMetadata>) -> Vec<JsonFeedItem> {
+    metadatas
+        .into_iter()
+        .filter(is_feed_article)
+        .map(|md| {
+            let link = article_link(feed.url, &md);
+            let content_html = article_content(feed, &md);
+            // Items need one of the two contents.
+            let content_text = content_html.is_none().then(|| md.description.clone());
+            let image = if md.cover_image.starts_with("http") {
+                Some(md.cover_image.clone())
+            } else {
+                cover_image_file(feed, &md).map(|(url, _)| url)
+            };
+            JsonFeedItem {
+                id: link.clone(),
+                url: link,
+                content_html,
+                content_text,
+                image,
+                authors: article_author(feed, &md)
+                    .map(|name| JsonFeedAuthor { name })
+                    .into_iter()
+                    .collect(),
+                tags: keywords(&md).map(str::to_string).collect(),
+                title: md.title,
+                summary: md.description,
+                date_published: md.published_at.map(|date| date.to_rfc3339()),
+                date_modified: md.last_modified_at.map(|date| date.to_rfc3339()),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dates::parse_date;
+    use chrono_tz::Tz;
+
+    #[test]
+    fn test_generate_json_feed() -> anyhow::Result<()> {
+        let tmp_dir = tempfile::TempDir::with_prefix("json_feed")?;
+        let output_path = tmp_dir.path().join("feed.json");
+        let article = PageMetadata {
+            title: "First Post".to_string(),
+            publish_date: "2024-01-01".to_string(),
+            published_at: parse_date("2024-01-01", Some(Tz::UTC)),
+            description: "About the first post".to_string(),
+            file_name: "first-post.html".to_string(),
+            content_html: "<p>Hello</p>".to_string(),
+            ..Default::default()
+        };
+        let error_page = PageMetadata {
+            file_name: "error.html".to_string(),
+            ..article.clone()
+        };
+
+        let feed = FeedInfo {
+            title: "Site".to_string(),
+            url: "https://example.com",
+            description: "A site",
+            language: "en-us",
+            author: None,
+            full_content: true,
+            output_dir: tmp_dir.path(),
+            link_by_file_name: false,
+        };
+        generate_json_feed(&feed, vec![article, error_page], &output_path)?;
+
+        let feed: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&output_path)?)?;
+        assert_eq!(feed["version"], JSON_FEED_VERSION);
+        assert_eq!(feed["feed_url"], "https://example.com/feed.json");
+        let items = feed["items"].as_array().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["id"], "https://example.com/first-post.html");
+        assert_eq!(items[0]["date_published"], "2024-01-01T00:00:00+00:00");
+        assert!(items[0].get("date_modified").is_none());
+        assert_eq!(items[0]["content_html"], "<p>Hello</p>");
+        assert!(items[0].get("content_text").is_none());
+        assert!(items[0].get("authors").is_none());
+        Ok(())
+    }
+}
@@ -1,28 +1,57 @@
 #[macro_use]
 extern crate log;
 
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::{fs, io};
 
 use anyhow::Context;
 
+pub use config::FeedFormat;
 pub use config::GeneretoConfig;
 pub use config::GeneretoConfigBlog;
+pub use config::GeneretoConfigHighlight;
+pub use config::GeneretoConfigMarkdown;
+pub use config::GeneretoConfigMarkdownOverrides;
+pub use config::GeneretoConfigTaxonomy;
+pub use config::HighlightStyle;
+pub use new_content::{new_content, ContentKind};
 pub use project_generation::generate_project;
 pub mod blog;
 
-use crate::fs_util::copy_directory_recursively;
-use crate::jinja_processor::SiteContext;
-use crate::rss_generation::generate_rss;
+use crate::atom_generation::generate_atom;
+use crate::build_cache::{BuildCache, CompiledPage};
+use crate::fs_util::{collect_files, copy_directory_recursively, copy_file_if_changed};
+use crate::jinja_processor::JinjaEnvironment;
+use crate::json_feed_generation::generate_json_feed;
+use crate::page_metadata::PageMetadata;
+use crate::parser::MarkdownOptions;
+use crate::rss_generation::{generate_rss, FeedInfo};
+use rayon::prelude::*;
 
+mod atom_generation;
+mod build_cache;
 mod config;
+mod dates;
 mod fs_util;
+mod git_history;
+mod highlighting;
+mod jinja_functions;
 pub mod jinja_processor;
+mod json_feed_generation;
+mod new_content;
 mod page_metadata;
 pub mod parser;
 mod project_generation;
 mod rss_generation;
+mod search_index;
+pub mod serve;
+mod shortcodes;
+mod site_collections;
+mod sitemap_generation;
+mod taxonomy;
 pub mod verify;
+pub mod watch;
 
 const PAGE_TEMPLATE_FILENAME: &str = "index.html";
 
@@ -56,122 +85,269 @@ impl DraftsOptions {
 /// project: path to the project.
 pub fn run(project_path: PathBuf, drafts_options: DraftsOptions) -> anyhow::Result<PathBuf> {
     let genereto_config = GeneretoConfig::load_from_folder(project_path)?;
+    build(&genereto_config, &drafts_options)
+}
+
+/// Builds the website described by an already loaded config.
+/// Useful when some config values are overridden, e.g. the url while serving locally.
+pub fn build(
+    genereto_config: &GeneretoConfig,
+    drafts_options: &DraftsOptions,
+) -> anyhow::Result<PathBuf> {
     debug!("GeneretoConfig: {genereto_config:?}");
-    if genereto_config.output_dir_path.exists() {
-        fs::remove_dir_all(&genereto_config.output_dir_path)?;
+    git_history::clear();
+    let mut build_cache = BuildCache::load(genereto_config, drafts_options)?;
+    // Template assets come first: feeds read the size of the cover images they link.
+    copy_folders_from_template(
+        &genereto_config.template_dirs,
+        &genereto_config.output_dir_path,
+        &mut build_cache,
+    )?;
+    highlighting::generate_theme_css(genereto_config, &mut build_cache)?;
+    let mut pages = vec![];
+    collect_pages(
+        genereto_config,
+        &genereto_config.content_path,
+        &mut pages,
+        &mut build_cache,
+    )?;
+    let mut jinja = JinjaEnvironment::from_config(genereto_config);
+    // Jinja templates can list every page, so all the metadata is read before rendering.
+    if let Some(jinja) = &mut jinja {
+        site_collections::load_site_collections(genereto_config, drafts_options, &pages, jinja)?;
     }
-    fs::create_dir_all(&genereto_config.output_dir_path)?;
-
-    let metadata = blog::generate_blog(&genereto_config, &drafts_options)?;
-    let has_blog = metadata.is_some();
-    if has_blog {
-        generate_rss(
-            &genereto_config.title,
-            &genereto_config.url,
-            &genereto_config.description,
-            metadata.unwrap(),
-            &genereto_config.output_dir_path,
-        )?;
+
+    let metadata = blog::generate_blog_with_cache(
+        genereto_config,
+        drafts_options,
+        jinja.as_ref(),
+        Some(&mut build_cache),
+    )?;
+    if let Some(metadata) = &metadata {
+        generate_feeds(genereto_config, metadata, &mut build_cache)?;
     }
-    compile_pages(&genereto_config, &drafts_options)?;
+    let pages = compile_pages(
+        genereto_config,
+        drafts_options,
+        pages,
+        jinja.as_ref(),
+        &mut build_cache,
+    )?;
 
-    copy_folders_from_template(
-        &genereto_config.template_dir_path,
-        &genereto_config.output_dir_path,
+    let all_pages: Vec<&PageMetadata> = metadata.iter().flatten().chain(&pages).collect();
+    taxonomy::generate_taxonomies(
+        genereto_config,
+        drafts_options,
+        jinja.as_ref(),
+        &all_pages,
+        &mut build_cache,
+    )?;
+    sitemap_generation::generate_sitemap(
+        genereto_config,
+        drafts_options,
+        &all_pages,
+        &mut build_cache,
+    )?;
+    search_index::generate_search_index(
+        genereto_config,
+        drafts_options,
+        &all_pages,
+        &mut build_cache,
     )?;
-    Ok(genereto_config.output_dir_path)
+
+    build_cache.save()?;
+    Ok(genereto_config.output_dir_path.clone())
 }
 
-/// Used to copy resources and assets from the template folder
-fn copy_folders_from_template(template_dir_path: &Path, output_dir_path: &Path) -> io::Result<()> {
-    for entry in fs::read_dir(template_dir_path)? {
-        let entry_path = entry?.path();
-        let entry_path_name = entry_path.file_name().unwrap().to_str().unwrap();
-        if entry_path.is_dir() {
-            copy_directory_recursively(
-                entry_path.as_ref(),
-                output_dir_path.join(entry_path_name).as_ref(),
-            )?;
+/// Writes the blog feeds picked in the config to the output folder.
+fn generate_feeds(
+    genereto_config: &GeneretoConfig,
+    metadata: &[PageMetadata],
+    build_cache: &mut BuildCache,
+) -> anyhow::Result<()> {
+    let feed_info = FeedInfo::new(genereto_config);
+    for feed in &genereto_config.feeds {
+        let feed_path = genereto_config.output_dir_path.join(feed.file_name());
+        let generate = match feed {
+            FeedFormat::Rss => generate_rss,
+            FeedFormat::Atom => generate_atom,
+            FeedFormat::Json => generate_json_feed,
+        };
+        generate(&feed_info, metadata.to_vec(), &feed_path)
+            .with_context(|| format!("Failed to write the feed {feed_path:?}"))?;
+        build_cache.record_output(&feed_path);
+    }
+    Ok(())
+}
+
+/// Used to copy resources and assets from the template folder and the themes. Each file is
+/// copied from the first folder which has it, so the template folder overrides the themes.
+fn copy_folders_from_template(
+    template_dirs: &[PathBuf],
+    output_dir_path: &Path,
+    build_cache: &mut BuildCache,
+) -> io::Result<()> {
+    // Relative path of each asset, with the file it is copied from.
+    let mut assets = BTreeMap::new();
+    for template_dir in template_dirs.iter().filter(|dir| dir.is_dir()) {
+        for entry in fs::read_dir(template_dir)? {
+            let entry_path = entry?.path();
+            let entry_path_name = entry_path.file_name().unwrap().to_str().unwrap();
+            // Shortcodes are templates, not resources of the website.
+            if entry_path.is_dir() && entry_path_name != shortcodes::SHORTCODES_DIR {
+                collect_files(&entry_path, Path::new(entry_path_name), &mut assets)?;
+            }
         }
     }
+    let mut copied = vec![];
+    for (relative_path, source_path) in assets {
+        let destination_path = output_dir_path.join(relative_path);
+        if let Some(parent) = destination_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        copy_file_if_changed(&source_path, &destination_path)?;
+        copied.push(destination_path);
+    }
+    build_cache.record_assets(copied);
     Ok(())
 }
 
+/// Compiles the markdown `pages` outside of the blog, found by `collect_pages`.
 fn compile_pages(
     genereto_config: &GeneretoConfig,
     drafts_options: &DraftsOptions,
-) -> anyhow::Result<()> {
-    // Create site context for Jinja rendering if enabled
-    let site_context = if genereto_config.enable_jinja {
-        Some(SiteContext::new(
-            &genereto_config.title,
-            &genereto_config.url,
-            &genereto_config.description,
-        ))
-    } else {
-        None
-    };
-
+    pages: Vec<(PathBuf, PathBuf)>,
+    jinja: Option<&JinjaEnvironment>,
+    build_cache: &mut BuildCache,
+) -> anyhow::Result<Vec<PageMetadata>> {
     // Load the default template once before the loop
-    let default_template =
-        parser::load_template(&genereto_config.template_dir_path, PAGE_TEMPLATE_FILENAME)?;
+    let template_dirs = &genereto_config.template_dirs;
+    let default_template = parser::load_template_in(template_dirs, PAGE_TEMPLATE_FILENAME)?;
+    let markdown_options = MarkdownOptions::new(genereto_config);
+    let templates_hash = format!(
+        "{}{}",
+        build_cache::hash_templates(template_dirs)?,
+        build_cache::hash_site_collections(jinja)?
+    );
 
-    for entry in fs::read_dir(&genereto_config.content_path)? {
-        let entry_path = entry?.path();
-        let entry_path_name = entry_path.file_name().unwrap().to_str().unwrap();
-        let destination_path = genereto_config.get_dest_path(&entry_path);
-
-        if entry_path.is_dir() {
-            if entry_path_name == "blog" {
-                debug!("Skipping blog directory from compile pages.");
-                continue;
-            }
-            copy_directory_recursively(&entry_path, &destination_path)?;
-        } else if entry_path.is_file() && entry_path.extension().unwrap_or_default() == "md" {
+    // Pages are independent from each other, so they are compiled in parallel.
+    let compiled_pages = pages
+        .into_par_iter()
+        .map(|(entry_path, destination_path)| {
             // Read source content and parse metadata first to check for custom template
             let source_content = fs::read_to_string(&entry_path)
                 .with_context(|| format!("Failed to read page {entry_path:?}"))?;
-            let (intermediate_content, metadata_raw) =
+            let (intermediate_content, mut metadata_raw) =
                 parser::compile_page_phase_1(&source_content)
                     .with_context(|| format!("Failed to parse page {entry_path:?}"))?;
 
+            // Expired pages are rendered with the expired template, if there is one.
+            if let Some(expired_template) = &genereto_config.expired_template {
+                if metadata_raw.has_expired(genereto_config.timezone) {
+                    metadata_raw.template_file = Some(expired_template.clone());
+                }
+            }
+
             // Use custom template if specified, otherwise use default
             let template_raw = if let Some(ref template_file) = metadata_raw.template_file {
-                parser::load_template(&genereto_config.template_dir_path, template_file)
-                    .with_context(|| {
-                        format!(
-                            "Page '{}' specifies template '{}' which could not be loaded",
-                            entry_path.display(),
-                            template_file
-                        )
-                    })?
+                parser::load_template_in(template_dirs, template_file).with_context(|| {
+                    format!(
+                        "Page '{}' specifies template '{}' which could not be loaded",
+                        entry_path.display(),
+                        template_file
+                    )
+                })?
             } else {
                 default_template.clone()
             };
 
-            // Compile phase 2 with the selected template
-            let (content, metadata) = parser::compile_page_phase_2(
-                intermediate_content,
-                &template_raw,
-                metadata_raw,
-                "",
-                &entry_path,
-                &genereto_config.url,
-                site_context.as_ref(),
-            )
-            .with_context(|| format!("Failed to compile page {entry_path:?}"))?;
-
-            // Handle drafts and write output
-            if metadata.is_draft && drafts_options.is_hide() {
+            let template_name = metadata_raw
+                .template_file
+                .clone()
+                .unwrap_or_else(|| PAGE_TEMPLATE_FILENAME.to_string());
+            let source_hash = build_cache::hash(source_content.as_bytes());
+            let template_hash =
+                build_cache::hash(format!("{template_raw}{templates_hash}").as_bytes());
+            let mut metadata = match build_cache.get(&entry_path, &source_hash, &template_hash) {
+                Some(metadata) => metadata,
+                None => {
+                    // Compile phase 2 with the selected template
+                    let (content, metadata) = parser::compile_page_phase_2(
+                        intermediate_content,
+                        &template_raw,
+                        &template_name,
+                        metadata_raw,
+                        "",
+                        &entry_path,
+                        &genereto_config.url,
+                        jinja,
+                        &markdown_options,
+                    )
+                    .with_context(|| format!("Failed to compile page {entry_path:?}"))?;
+
+                    // Handle drafts and write output
+                    if metadata.is_draft && drafts_options.is_hide() {
+                        return Ok(None);
+                    }
+                    fs::write(&destination_path, content)
+                        .with_context(|| format!("Failed to write page to {destination_path:?}"))?;
+                    metadata
+                }
+            };
+            metadata.path = genereto_config.get_output_relative_path(&destination_path);
+            Ok(Some(CompiledPage {
+                source: entry_path,
+                source_hash,
+                template_hash,
+                destination: destination_path,
+                metadata,
+            }))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let mut metadatas = vec![];
+    for page in compiled_pages.into_iter().flatten() {
+        metadatas.push(page.metadata.clone());
+        build_cache.insert(page);
+    }
+
+    Ok(metadatas)
+}
+
+/// Walks `dir` recursively, collecting the markdown pages with their destination path.
+/// Directories are mirrored in the output folder and any other file is copied as an asset.
+fn collect_pages(
+    genereto_config: &GeneretoConfig,
+    dir: &Path,
+    pages: &mut Vec<(PathBuf, PathBuf)>,
+    build_cache: &mut BuildCache,
+) -> anyhow::Result<()> {
+    for entry_path in fs_util::sorted_dir_entries(dir)? {
+        let entry_path_name = entry_path.file_name().unwrap().to_str().unwrap();
+        let destination_path = genereto_config.get_dest_path(&entry_path);
+
+        if entry_path.is_dir() {
+            if dir == genereto_config.content_path && entry_path_name == "blog" {
+                debug!("Skipping blog directory from compile pages.");
                 continue;
             }
-            fs::write(&destination_path, content)
-                .with_context(|| format!("Failed to write page to {destination_path:?}"))?;
+            if entry_path_name == ".git" {
+                continue;
+            }
+            fs::create_dir_all(&destination_path)
+                .with_context(|| format!("Failed to create directory {destination_path:?}"))?;
+            collect_pages(genereto_config, &entry_path, pages, build_cache)?;
+        } else if entry_path.is_file() && entry_path.extension().unwrap_or_default() == "md" {
+            pages.push((entry_path, destination_path));
+        } else if entry_path.is_file() {
+            let mut copied = vec![];
+            copy_directory_recursively(&entry_path, &destination_path, &mut copied)
+                .with_context(|| format!("Failed to copy {entry_path:?}"))?;
+            build_cache.record_assets(copied);
         } else {
             warn!("Found entry which is not a file nor a directory: {entry_path:?}. Skipping.");
         }
     }
-
     Ok(())
 }
 
@@ -1,10 +1,9 @@
 use clap::{CommandFactory, Parser, Subcommand};
 use env_logger::Env;
-use genereto::run;
 use genereto::verify::{self, Check};
-use genereto::DraftsOptions;
-use log::info;
-use std::path::PathBuf;
+use genereto::{ContentKind, DraftsOptions};
+use log::{error, info};
+use std::path::{Path, PathBuf};
 
 /// Genereto is a super simple static site generator.
 #[derive(Parser)]
@@ -23,6 +22,14 @@ struct Cli {
     #[arg(long = "verify", value_enum, num_args = 0..)]
     verify_checks: Option<Vec<Check>>,
 
+    /// Number of threads used to compile pages. Defaults to the number of CPUs.
+    #[arg(long, global = true)]
+    jobs: Option<usize>,
+
+    /// Where to write the generated website. Overrides `output_dirname` from config.yml.
+    #[arg(long, global = true)]
+    output: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -38,6 +45,20 @@ enum Commands {
         #[arg(long)]
         override_git: bool,
     },
+    /// Creates a new post or page from the archetypes of the project
+    New {
+        /// What to create: a blog post, dated today, or a page
+        #[arg(value_enum)]
+        kind: ContentKind,
+        /// Title of the new content, its file name is derived from it
+        title: String,
+        /// Path to the genereto project folder
+        #[arg(long, default_value = ".")]
+        project_path: PathBuf,
+        /// Also create the folder for the images of the new content, next to it
+        #[arg(long)]
+        assets: bool,
+    },
     /// Run verification checks on a built project
     Verify {
         /// Path to the genereto project folder
@@ -47,11 +68,51 @@ enum Commands {
         #[arg(value_enum)]
         checks: Vec<Check>,
     },
+    /// Builds the project in a temporary folder, serves it on localhost and reloads open pages
+    /// after every rebuild. The output folder is left as it is
+    Serve {
+        /// Path to the genereto project folder
+        #[arg(long)]
+        project_path: PathBuf,
+        /// How to handle draft pages
+        #[arg(long, value_enum, default_value_t = DraftsOptions::Dev)]
+        drafts_options: DraftsOptions,
+        /// Port of the local web server
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+    },
+    /// Builds the project and rebuilds it whenever content, templates or config change
+    Watch {
+        /// Path to the genereto project folder
+        #[arg(long)]
+        project_path: PathBuf,
+        /// How to handle draft pages
+        #[arg(long, value_enum, default_value_t = DraftsOptions::Build)]
+        drafts_options: DraftsOptions,
+    },
+}
+
+/// Loads the project config, applying the `--output` override if present.
+fn load_config(
+    project_path: &Path,
+    output: Option<&Path>,
+) -> anyhow::Result<genereto::GeneretoConfig> {
+    let config = genereto::GeneretoConfig::load_from_folder(project_path)?;
+    Ok(match output {
+        Some(output) => config.with_output_dir(output)?,
+        None => config,
+    })
 }
 
 fn main() {
     env_logger::Builder::from_env(Env::default().default_filter_or("debug")).init();
     let cli = Cli::parse();
+    if let Some(jobs) = cli.jobs {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(jobs)
+            .build_global()
+            .expect("Failed to create the thread pool");
+    }
 
     match cli.command {
         Some(Commands::GenerateProject {
@@ -61,11 +122,22 @@ fn main() {
             genereto::generate_project(&project_path, override_git).expect("Error");
             info!("Your project was successfully generated. Use `genereto --project-path {}` to run it.", project_path.display());
         }
+        Some(Commands::New {
+            kind,
+            title,
+            project_path,
+            assets,
+        }) => {
+            let config = load_config(&project_path, cli.output.as_deref())
+                .expect("Failed to load project config");
+            let path = genereto::new_content(&config, kind, &title, assets).expect("Error");
+            println!("Created {}", path.display());
+        }
         Some(Commands::Verify {
             project_path,
             checks,
         }) => {
-            let config = genereto::GeneretoConfig::load_from_folder(project_path)
+            let config = load_config(&project_path, cli.output.as_deref())
                 .expect("Failed to load project config");
             let output_dir = config.output_dir_path.clone();
             let issues = verify::run_checks(&config, &checks, &output_dir);
@@ -77,6 +149,25 @@ fn main() {
                 println!("All verification checks passed.");
             }
         }
+        Some(Commands::Serve {
+            project_path,
+            drafts_options,
+            port,
+        }) => {
+            let load_config = || load_config(&project_path, cli.output.as_deref());
+            genereto::serve::run(load_config, drafts_options, port).expect("Error");
+        }
+        Some(Commands::Watch {
+            project_path,
+            drafts_options,
+        }) => {
+            let load_config = || load_config(&project_path, cli.output.as_deref());
+            let config = load_config().expect("Failed to load project config");
+            if let Err(e) = genereto::build(&config, &drafts_options) {
+                error!("Build failed: {e:?}");
+            }
+            genereto::watch::watch(config, load_config, &drafts_options, |_| {});
+        }
         None => {
             let project_path = cli.project_path.unwrap_or_else(|| {
                 eprintln!("error: --project-path is required when not using a subcommand\n");
@@ -84,7 +175,9 @@ fn main() {
                 std::process::exit(1);
             });
 
-            let output_dir = run(project_path.clone(), cli.drafts_options).expect("Error");
+            let config = load_config(&project_path, cli.output.as_deref())
+                .expect("Failed to load project config");
+            let output_dir = genereto::build(&config, &cli.drafts_options).expect("Error");
             println!(
                 "Website generation completed. Index path: {} ",
                 output_dir.join("index.html").display()
@@ -92,8 +185,6 @@ fn main() {
 
             // Run verify checks if --verify was passed
             if let Some(checks) = cli.verify_checks {
-                let config = genereto::GeneretoConfig::load_from_folder(project_path)
-                    .expect("Failed to load project config");
                 let issues = verify::run_checks(&config, &checks, &output_dir);
                 let count = verify::report_issues(&issues);
                 if count > 0 {
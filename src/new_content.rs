@@ -0,0 +1,133 @@
+use crate::blog::BLOG_ENTRIES_FOLDER_RELATIVE_PATH;
+use crate::parser::get_anchor_id_from_title;
+use crate::GeneretoConfig;
+use anyhow::{bail, Context};
+use chrono::{Local, Utc};
+use std::fs;
+use std::path::PathBuf;
+
+/// Folder of the archetypes, inside the project folder.
+const ARCHETYPES: &str = "archetypes";
+
+const DEFAULT_POST_ARCHETYPE: &str = "---
+title: \"$GENERETO['title']\"
+publish_date: $GENERETO['publish_date']
+keywords: \"\"
+---
+
+";
+
+const DEFAULT_PAGE_ARCHETYPE: &str = "---
+title: \"$GENERETO['title']\"
+---
+
+";
+
+/// Kinds of content created by `genereto new`.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContentKind {
+    /// A blog article, written to content/blog/YYYY-MM-DD-slug.md
+    Post,
+    /// A page, written to content/slug.md
+    Page,
+}
+
+impl ContentKind {
+    fn archetype_file_name(&self) -> &'static str {
+        match self {
+            ContentKind::Post => "post.md",
+            ContentKind::Page => "page.md",
+        }
+    }
+
+    fn default_archetype(&self) -> &'static str {
+        match self {
+            ContentKind::Post => DEFAULT_POST_ARCHETYPE,
+            ContentKind::Page => DEFAULT_PAGE_ARCHETYPE,
+        }
+    }
+}
+
+/// Creates a new post or page titled `title` from the archetype of its kind, like
+/// `archetypes/post.md`, or from a minimal frontmatter if the project has none.
+/// The markers `$GENERETO['title']`, `$GENERETO['publish_date']` (today, in the timezone of
+/// the config) and `$GENERETO['page_name']` of the archetype are replaced. Inside double
+/// quotes, like `title: "$GENERETO['title']"`, the title is escaped to stay a valid string.
+/// If `create_asset_folder` is true, the sibling folder for the images of the page is created
+/// too. Returns the path of the new file.
+pub fn new_content(
+    genereto_config: &GeneretoConfig,
+    kind: ContentKind,
+    title: &str,
+    create_asset_folder: bool,
+) -> anyhow::Result<PathBuf> {
+    let slug = get_anchor_id_from_title(title);
+    if slug.is_empty() {
+        bail!("Can't make a file name out of the title '{title}'.");
+    }
+    let now = Utc::now();
+    let today = match genereto_config.timezone {
+        Some(timezone) => now.with_timezone(&timezone).date_naive(),
+        None => now.with_timezone(&Local).date_naive(),
+    }
+    .format("%Y-%m-%d")
+    .to_string();
+    let (folder, page_name) = match kind {
+        ContentKind::Post => (
+            genereto_config
+                .content_path
+                .join(BLOG_ENTRIES_FOLDER_RELATIVE_PATH),
+            format!("{today}-{slug}"),
+        ),
+        ContentKind::Page => (genereto_config.content_path.clone(), slug),
+    };
+    let path = folder.join(format!("{page_name}.md"));
+    if path.exists() {
+        bail!("{path:?} already exists.");
+    }
+
+    let archetype_path = genereto_config
+        .project_path
+        .join(ARCHETYPES)
+        .join(kind.archetype_file_name());
+    let archetype = if archetype_path.exists() {
+        fs::read_to_string(&archetype_path)
+            .with_context(|| format!("Failed to read the archetype {archetype_path:?}"))?
+    } else {
+        kind.default_archetype().to_string()
+    };
+    let content = archetype
+        .replace(
+            "\"$GENERETO['title']\"",
+            &format!("\"{}\"", escape_double_quoted(title.trim())),
+        )
+        .replace("$GENERETO['title']", title.trim())
+        .replace("$GENERETO['publish_date']", &today)
+        .replace("$GENERETO['page_name']", &page_name);
+
+    fs::create_dir_all(&folder).with_context(|| format!("Failed to create {folder:?}"))?;
+    fs::write(&path, content).with_context(|| format!("Failed to write {path:?}"))?;
+    if create_asset_folder {
+        let asset_folder = folder.join(&page_name);
+        fs::create_dir_all(&asset_folder)
+            .with_context(|| format!("Failed to create {asset_folder:?}"))?;
+    }
+    Ok(path)
+}
+
+/// Escapes `text` for a double-quoted YAML, TOML or JSON string.
+fn escape_double_quoted(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
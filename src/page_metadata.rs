@@ -1,7 +1,13 @@
-use crate::parser::get_anchor_id_from_title;
-use chrono::{Datelike, NaiveDate};
+use crate::config::{GeneretoConfigMarkdown, GeneretoConfigMarkdownOverrides};
+use crate::dates::parse_date;
+use crate::parser::{get_anchor_id_from_title, markdown_extensions};
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, Utc};
+use chrono_tz::Tz;
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::borrow::Cow;
 use std::fmt::{Display, Formatter};
 use std::path::Path;
 
@@ -14,9 +20,11 @@ use std::collections::HashMap;
 pub struct PageMetadataRaw {
     /// Title of the page
     pub title: String,
-    /// Publish date as string
+    /// Publish date as string, a day like 2024-01-15 or a time like 2024-01-15T10:30:00+02:00
     #[serde(default)]
     pub publish_date: String,
+    /// After this date the page is hidden like a draft, e.g. for events and offers.
+    pub expiry_date: Option<String>,
     /// Defaults to false. If true, this article will not be processed.
     #[serde(default = "bool::default")]
     pub is_draft: bool,
@@ -36,9 +44,30 @@ pub struct PageMetadataRaw {
     pub url: Option<String>,
     /// Optional template file override for this specific page
     pub template_file: Option<String>,
-    /// Custom metadata fields that will be available as $GENERETO['field_name']
+    /// Defaults to true. If false, the page is not listed in sitemap.xml.
+    #[serde(default = "default_sitemap")]
+    pub sitemap: bool,
+    /// Priority of the page in sitemap.xml, between 0.0 and 1.0.
+    pub priority: Option<f32>,
+    /// Markdown extensions of this page, overriding the ones of the config.
+    pub markdown: Option<GeneretoConfigMarkdownOverrides>,
+    /// Custom metadata fields that will be available as $GENERETO['field_name'].
+    /// Values keep their type, like lists and maps, and nested fields are available as
+    /// $GENERETO['field_name.nested'].
     #[serde(flatten)]
-    pub custom_metadata: HashMap<String, String>,
+    pub custom_metadata: HashMap<String, Value>,
+}
+
+fn default_sitemap() -> bool {
+    true
+}
+
+impl PageMetadataRaw {
+    /// True if the expiry date of the page has passed.
+    pub(crate) fn has_expired(&self, timezone: Option<Tz>) -> bool {
+        let expiry_date = self.expiry_date.as_deref().unwrap_or_default();
+        has_expired(parse_date(expiry_date, timezone))
+    }
 }
 
 impl Display for PageMetadataRaw {
@@ -48,10 +77,21 @@ impl Display for PageMetadataRaw {
 }
 
 /// Derived from PageMetadata and few more fields
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PageMetadata {
     pub title: String,
+    /// Publish date as written in the frontmatter.
     pub publish_date: String,
+    /// Parsed publish date, None if missing or malformed. Used for sorting, feeds and to
+    /// hold back the pages scheduled for later.
+    #[serde(default)]
+    pub published_at: Option<DateTime<FixedOffset>>,
+    /// Expiry date as written in the frontmatter, empty if the page doesn't expire.
+    #[serde(default)]
+    pub expiry_date: String,
+    /// Parsed expiry date. Expired pages are drafts.
+    #[serde(default)]
+    pub expires_at: Option<DateTime<FixedOffset>>,
     pub keywords: String,
     /// Reading time in minutes
     pub reading_time_mins: String,
@@ -62,10 +102,17 @@ pub struct PageMetadata {
     /// Page name without extension (e.g., "2026-03-29-my-article" for "2026-03-29-my-article.md")
     /// Useful for referencing sibling asset directories in markdown content.
     pub page_name: String,
+    /// Path of the generated page relative to the output folder, e.g. "blog/my-article.html".
+    /// Empty for entries without a page of their own, like the ones from blog.yml.
+    #[serde(default)]
+    pub path: String,
     /// Table of contents generated from headings.
     pub table_of_contents: String,
     /// Derived from git.
     pub last_modified_date: String,
+    /// Parsed last modified date.
+    #[serde(default)]
+    pub last_modified_at: Option<DateTime<FixedOffset>>,
     pub cover_image: String,
     pub is_draft: bool,
     pub add_title: bool,
@@ -75,8 +122,20 @@ pub struct PageMetadata {
     pub website_url: String,
     /// Optional template file override for this specific page
     pub template_file: Option<String>,
+    /// If true, the page is not listed in sitemap.xml.
+    #[serde(default)]
+    pub exclude_from_sitemap: bool,
+    /// Priority of the page in sitemap.xml.
+    #[serde(default)]
+    pub sitemap_priority: Option<f32>,
     /// Custom metadata fields that will be available as $GENERETO['field_name']
-    pub custom_metadata: HashMap<String, String>,
+    pub custom_metadata: HashMap<String, Value>,
+    /// Rendered html of the page content, without the template. Used by feeds.
+    #[serde(default)]
+    pub content_html: String,
+    /// Plain text of the page content. Used by the search index.
+    #[serde(default)]
+    pub content_text: String,
 }
 
 impl PageMetadata {
@@ -86,15 +145,25 @@ impl PageMetadata {
         file_path: &Path,
         default_cover_image: &str,
         website_url: &str,
+        markdown: &GeneretoConfigMarkdown,
+        timezone: Option<Tz>,
     ) -> Self {
         let file_stem = file_path.file_stem().unwrap().to_str().unwrap().to_string();
         let file_name = format!("{}.html", &file_stem);
-        let table_of_contents = page_metadata
-            .show_table_of_contents
-            .then(|| generate_table_of_contents(page_content))
-            .unwrap_or_default();
+        let table_of_contents = if page_metadata.show_table_of_contents {
+            generate_table_of_contents(page_content, markdown)
+        } else {
+            String::new()
+        };
         let has_todos = contains_todos(page_content);
-        let has_future_date = is_future_date(&page_metadata.publish_date);
+        let published_at = parse_date(&page_metadata.publish_date, timezone);
+        let has_future_date = is_future_date(published_at);
+        let expiry_date = page_metadata.expiry_date.take().unwrap_or_default();
+        let expires_at = parse_date(&expiry_date, timezone);
+        if expires_at.is_none() && !expiry_date.is_empty() {
+            warn!("File {file_name} has an invalid expiry_date: {expiry_date}.");
+        }
+        let is_expired = has_expired(expires_at);
 
         if has_todos && !page_metadata.is_draft {
             info!("File {} has todos - setting is_draft to true.", file_name);
@@ -106,18 +175,27 @@ impl PageMetadata {
             );
         }
 
+        if is_expired && !page_metadata.is_draft {
+            info!("File {} has expired - setting is_draft to true.", file_name);
+        }
+
         page_metadata.is_draft = page_metadata.is_draft || has_todos || has_future_date;
         page_metadata.title = if page_metadata.is_draft {
             format!("[DRAFT] {}", page_metadata.title)
         } else {
             page_metadata.title
         };
+        // Expired pages are hidden like drafts, but they are not work in progress.
+        page_metadata.is_draft = page_metadata.is_draft || is_expired;
+        let last_modified_date =
+            get_last_modified_date(&page_metadata.publish_date, published_at, file_path);
         Self {
-            last_modified_date: get_last_modified_date(&page_metadata.publish_date, file_path),
+            last_modified_at: parse_date(&last_modified_date, timezone),
+            last_modified_date,
             reading_time_mins: estimate_reading_time(page_content).to_string(),
             description: page_metadata
                 .description
-                .unwrap_or_else(|| get_description(page_content, DESCRIPTION_LENGTH)),
+                .unwrap_or_else(|| get_description(page_content, DESCRIPTION_LENGTH, markdown)),
             cover_image: Self::get_cover_image(
                 default_cover_image,
                 page_metadata.cover_image.as_ref(),
@@ -127,15 +205,23 @@ impl PageMetadata {
             title: page_metadata.title,
             keywords: page_metadata.keywords,
             publish_date: page_metadata.publish_date,
+            published_at,
+            expiry_date,
+            expires_at,
             is_draft: page_metadata.is_draft,
             add_title: page_metadata.add_title,
             file_name,
             page_name: file_stem,
+            path: String::new(),
             table_of_contents,
             article_url: page_metadata.url,
             website_url: website_url.to_string(),
             template_file: page_metadata.template_file,
+            exclude_from_sitemap: !page_metadata.sitemap,
+            sitemap_priority: page_metadata.priority,
             custom_metadata: page_metadata.custom_metadata,
+            content_html: String::new(),
+            content_text: String::new(),
         }
     }
     fn get_cover_image(
@@ -170,6 +256,7 @@ impl PageMetadata {
         let mut variables = vec![
             ("$GENERETO['title']", self.title.trim().to_string()),
             ("$GENERETO['publish_date']", self.publish_date.clone()),
+            ("$GENERETO['publish_datetime']", self.publish_datetime()),
             (
                 "$GENERETO['last_modified_date']",
                 self.last_modified_date.clone(),
@@ -197,6 +284,10 @@ impl PageMetadata {
             ),
         ];
 
+        if !self.expiry_date.is_empty() {
+            variables.push(("$GENERETO['expiry_date']", self.expiry_date.clone()));
+        }
+
         // Only include article_url if it has a value, so custom_metadata
         // entries using `article_url:` key are not clobbered with empty string.
         if let Some(ref article_url) = self.article_url {
@@ -210,11 +301,47 @@ impl PageMetadata {
 
         // Add custom metadata variables
         for (key, value) in &self.custom_metadata {
-            variables.push((format!("$GENERETO['{key}']"), value.clone()));
+            push_custom_variables(key, value, &mut variables);
         }
         variables
     }
 
+    /// Publish date in the RFC 3339 format, like 2024-01-15T10:30:00+02:00. Empty if the page
+    /// has no valid publish date.
+    pub(crate) fn publish_datetime(&self) -> String {
+        self.published_at
+            .map(|date| date.to_rfc3339())
+            .unwrap_or_default()
+    }
+
+    /// True if the expiry date of the page has passed.
+    pub(crate) fn has_expired(&self) -> bool {
+        has_expired(self.expires_at)
+    }
+
+    /// Text of the custom metadata `key`, see `custom_value_to_string`.
+    pub(crate) fn custom_text(&self, key: &str) -> Option<String> {
+        self.custom_metadata.get(key).map(custom_value_to_string)
+    }
+
+    /// Terms of `taxonomy` used by this page, read from the frontmatter field with the
+    /// same name. Terms are a list or a comma separated string.
+    pub(crate) fn taxonomy_terms(&self, taxonomy: &str) -> Vec<String> {
+        let terms = match self.custom_metadata.get(taxonomy) {
+            Some(Value::Array(terms)) => terms.iter().map(custom_value_to_string).collect(),
+            Some(terms) => custom_value_to_string(terms)
+                .split(',')
+                .map(str::to_string)
+                .collect(),
+            None => vec![],
+        };
+        terms
+            .into_iter()
+            .map(|term| term.trim().to_string())
+            .filter(|term| !term.is_empty())
+            .collect()
+    }
+
     // Apply variables to the final page.
     pub(crate) fn apply(&self, mut final_page: String) -> String {
         for (key, value) in self.get_variables() {
@@ -224,15 +351,56 @@ impl PageMetadata {
     }
 }
 
+/// Text of a custom metadata value in marker templates: lists are joined with ", ", maps are
+/// written as JSON and null is empty.
+pub(crate) fn custom_value_to_string(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(value) => value.clone(),
+        Value::Array(values) => values
+            .iter()
+            .map(custom_value_to_string)
+            .collect::<Vec<_>>()
+            .join(", "),
+        value => value.to_string(),
+    }
+}
+
+/// Adds the variable of a custom metadata value, and the ones of its nested values with a
+/// dotted name like $GENERETO['series.name'] or $GENERETO['authors.0'].
+fn push_custom_variables(name: &str, value: &Value, variables: &mut Vec<(String, String)>) {
+    variables.push((
+        format!("$GENERETO['{name}']"),
+        custom_value_to_string(value),
+    ));
+    match value {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                push_custom_variables(&format!("{name}.{key}"), value, variables);
+            }
+        }
+        Value::Array(values) => {
+            for (idx, value) in values.iter().enumerate() {
+                push_custom_variables(&format!("{name}.{idx}"), value, variables);
+            }
+        }
+        _ => (),
+    }
+}
+
 impl PartialOrd for PageMetadata {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.publish_date.cmp(&other.publish_date).reverse())
+        let key = (self.published_at, &self.publish_date);
+        Some(
+            key.cmp(&(other.published_at, &other.publish_date))
+                .reverse(),
+        )
     }
 }
 
 impl PartialEq for PageMetadata {
     fn eq(&self, other: &Self) -> bool {
-        self.publish_date == other.publish_date
+        self.published_at == other.published_at && self.publish_date == other.publish_date
     }
 }
 
@@ -246,38 +414,34 @@ impl Display for PageMetadata {
 
 /// Get last modified date of the file as string
 /// Uses git to get last modified date. It will return the most recent date between the last update and the publish date
-fn get_last_modified_date(publish_date: &str, file_path: &Path) -> String {
-    let last_modified_date = get_last_modified_date_of_file_from_git(file_path)
-        .unwrap_or_else(|| publish_date.to_string());
-    if last_modified_date.is_empty() {
+/// Git only knows the day, so a page updated the day it is published keeps its publish date.
+fn get_last_modified_date(
+    publish_date: &str,
+    published_at: Option<DateTime<FixedOffset>>,
+    file_path: &Path,
+) -> String {
+    let Some(last_modified_date) = crate::git_history::last_modified_date(file_path) else {
+        // Not committed yet, outside of a git repository or git is not available.
         return publish_date.to_string();
-    }
-    let publish_date = if publish_date.is_empty() {
-        &last_modified_date
-    } else {
-        publish_date
     };
-    let last_update_as_date = NaiveDate::parse_from_str(&last_modified_date, "%Y-%m-%d").unwrap();
-    let publish_date_as_date = NaiveDate::parse_from_str(publish_date, "%Y-%m-%d").unwrap();
-    if last_update_as_date < publish_date_as_date {
-        publish_date.to_string()
-    } else {
-        last_modified_date
+    let last_update_as_date = NaiveDate::parse_from_str(&last_modified_date, "%Y-%m-%d");
+    match (last_update_as_date, published_at) {
+        (Ok(last_update), Some(publish)) if last_update <= publish.date_naive() => {
+            publish_date.to_string()
+        }
+        (Ok(_), _) => last_modified_date,
+        (Err(_), _) => publish_date.to_string(),
     }
 }
 
 /// Check if the publish date is in the future
-fn is_future_date(publish_date: &str) -> bool {
-    if publish_date.is_empty() {
-        return false;
-    }
-
-    if let Ok(date) = NaiveDate::parse_from_str(publish_date, "%Y-%m-%d") {
-        let today = chrono::Local::now().date_naive();
-        return date > today;
-    }
+fn is_future_date(published_at: Option<DateTime<FixedOffset>>) -> bool {
+    published_at.is_some_and(|date| date > Utc::now())
+}
 
-    false
+/// Check if the expiry date is in the past
+fn has_expired(expires_at: Option<DateTime<FixedOffset>>) -> bool {
+    expires_at.is_some_and(|date| date <= Utc::now())
 }
 
 /// Search page_content for "$GENERETO{TO DO: string.
@@ -305,7 +469,9 @@ pub(crate) fn estimate_reading_time(page_content: &str) -> u16 {
 /// 1. remove links if any. Otherwise the markdown will end up in the preview.
 /// 2. remove any whitespace at the start and at the end.
 /// 3. remove any markdown if it's a title.
-fn get_description(article: &str, limit: usize) -> String {
+/// 4. remove the metadata blocks and footnotes, if their extensions are enabled.
+fn get_description(article: &str, limit: usize, extensions: &GeneretoConfigMarkdown) -> String {
+    let article = without_extension_blocks(article, extensions);
     let mut buff = String::new();
     for line in article.lines() {
         if line.trim().starts_with('#') {
@@ -319,21 +485,78 @@ fn get_description(article: &str, limit: usize) -> String {
             break;
         }
     }
-    use pulldown_cmark::{Event, Parser};
-    // Extract plaintext
-    let parser = Parser::new(&buff);
+    // Extract plaintext, parsed like the body of the page.
+    let mut plaintext = String::new();
+    for event in Parser::new_ext(&buff, markdown_extensions(extensions)) {
+        push_plain_text(event, &mut plaintext);
+    }
+
+    truncate_text(plaintext.trim(), limit)
+}
+
+/// `markdown` without what the enabled extensions don't render as text of the page: metadata
+/// blocks, footnote definitions and references. Unchanged if those extensions are disabled.
+fn without_extension_blocks<'a>(
+    markdown: &'a str,
+    extensions: &GeneretoConfigMarkdown,
+) -> Cow<'a, str> {
+    if !extensions.metadata_blocks && !extensions.footnotes {
+        return Cow::Borrowed(markdown);
+    }
+    let mut text = String::new();
+    let mut end = 0;
+    for (event, range) in
+        Parser::new_ext(markdown, markdown_extensions(extensions)).into_offset_iter()
+    {
+        let is_skipped = matches!(
+            event,
+            Event::Start(Tag::MetadataBlock(_) | Tag::FootnoteDefinition(_))
+                | Event::FootnoteReference(_)
+        );
+        if is_skipped && range.start >= end {
+            text.push_str(&markdown[end..range.start]);
+            end = range.end;
+        }
+    }
+    text.push_str(&markdown[end..]);
+    Cow::Owned(text)
+}
+
+/// Text of the whole markdown content, without formatting, html and extra whitespace.
+pub(crate) fn get_plain_text(markdown: &str, extensions: &GeneretoConfigMarkdown) -> String {
     let mut plaintext = String::new();
-    for event in parser {
+    let mut in_metadata_block = false;
+    for event in Parser::new_ext(markdown, markdown_extensions(extensions)) {
         match event {
-            Event::Text(text) => plaintext.push_str(&text),
-            Event::Code(text) => plaintext.push_str(&text),
-            Event::Html(text) => plaintext.push_str(&text),
-            Event::SoftBreak | Event::HardBreak => plaintext.push('\n'),
-            _ => (),
+            Event::Start(Tag::MetadataBlock(_)) => in_metadata_block = true,
+            Event::End(TagEnd::MetadataBlock(_)) => in_metadata_block = false,
+            _ if in_metadata_block => (),
+            Event::Html(_) | Event::InlineHtml(_) => (),
+            // Blocks are separated, so that words of different blocks are not joined.
+            Event::End(
+                TagEnd::Paragraph
+                | TagEnd::Heading(_)
+                | TagEnd::Item
+                | TagEnd::DefinitionListTitle
+                | TagEnd::DefinitionListDefinition
+                | TagEnd::CodeBlock
+                | TagEnd::TableCell,
+            ) => plaintext.push(' '),
+            event => push_plain_text(event, &mut plaintext),
         }
     }
+    plaintext.split_whitespace().collect::<Vec<_>>().join(" ")
+}
 
-    truncate_text(plaintext.trim(), limit)
+fn push_plain_text(event: Event, plaintext: &mut String) {
+    match event {
+        Event::Text(text) => plaintext.push_str(&text),
+        Event::Code(text) => plaintext.push_str(&text),
+        Event::InlineMath(text) | Event::DisplayMath(text) => plaintext.push_str(&text),
+        Event::Html(text) => plaintext.push_str(&text),
+        Event::SoftBreak | Event::HardBreak => plaintext.push('\n'),
+        _ => (),
+    }
 }
 
 fn remove_links(buff: String) -> String {
@@ -366,24 +589,12 @@ fn truncate_text(article: &str, limit: usize) -> String {
     truncated
 }
 
-// On error, if git is not available in the system, it will return None.
-fn get_last_modified_date_of_file_from_git(file_path: &Path) -> Option<String> {
-    let mut git_cmd = std::process::Command::new("git");
-    git_cmd.current_dir(file_path.parent().unwrap());
-    git_cmd.arg("log");
-    git_cmd.arg("-1");
-    git_cmd.arg("--format=%cd");
-    git_cmd.arg("--date=short");
-    git_cmd.arg(file_path.file_name().unwrap());
-    let output = git_cmd.output().ok()?;
-    let date = String::from_utf8(output.stdout).unwrap().trim().to_string();
-    Some(date)
-}
-
 // TODO: What happens with overlaps of sections with same name?
 /// Generate the table of contents
 /// out is in html. I could change it to output markdown instead.
-fn generate_table_of_contents(markdown: &str) -> String {
+/// Headings in the metadata blocks, if their extension is enabled, are skipped.
+fn generate_table_of_contents(markdown: &str, extensions: &GeneretoConfigMarkdown) -> String {
+    let markdown = without_extension_blocks(markdown, extensions);
     let mut toc = String::new();
     let mut in_code_block = false;
     // current_depth = 1 is for a single '#' or an <h1>. HTML semantics wants that an article should
@@ -410,6 +621,16 @@ fn generate_table_of_contents(markdown: &str) -> String {
             let title = title.trim();
             let class_name = format!("table_of_contents-indent-{}", last_depth);
             let anchor = get_anchor_id_from_title(title);
+            // Quotes and dashes look like the ones of the heading.
+            let title = if extensions.smart_punctuation {
+                let mut smart_title = String::new();
+                for event in Parser::new_ext(title, Options::ENABLE_SMART_PUNCTUATION) {
+                    push_plain_text(event, &mut smart_title);
+                }
+                smart_title
+            } else {
+                title.to_string()
+            };
 
             toc.push_str(&format!(
                 "<li><a href=\"#{}\" class=\"{}\">{}</a>",
@@ -426,7 +647,10 @@ fn generate_table_of_contents(markdown: &str) -> String {
         current_depth -= 1;
     }
 
-    format!("<ul class=\"table_of_contents\">\n{}</ul>", &toc[6..])
+    format!(
+        "<ul class=\"table_of_contents\">\n{}</ul>",
+        toc.get(6..).unwrap_or_default()
+    )
 }
 
 // remove everything after the last occurrence of `character`. Check the tests.
@@ -440,9 +664,11 @@ fn remove_after_last_character(input: &str, character: char) -> String {
 
 #[cfg(test)]
 mod test {
+    use crate::config::GeneretoConfigMarkdown;
+    use crate::dates::parse_date;
     use crate::page_metadata::{
-        contains_todos, generate_table_of_contents, get_description, remove_after_last_character,
-        PageMetadata, PageMetadataRaw,
+        contains_todos, generate_table_of_contents, get_description, get_last_modified_date,
+        get_plain_text, remove_after_last_character, PageMetadata, PageMetadataRaw,
     };
     use std::assert_eq;
     use std::collections::HashMap;
@@ -467,7 +693,8 @@ mod test {
 </ul>
 <li><a href=\"#advanced-features\" class=\"table_of_contents-indent-1\">Advanced Features!!!!</a></ul>";
 
-        let table_of_contents = generate_table_of_contents(test_input);
+        let table_of_contents =
+            generate_table_of_contents(test_input, &GeneretoConfigMarkdown::default());
         assert_eq!(table_of_contents.trim(), expected.trim());
         let test_input2 = r#"
 ## Introduction
@@ -487,10 +714,29 @@ mod test {
 <ul>
 <li><a href=\"#advanced-features\" class=\"table_of_contents-indent-3\">Advanced Features!!!!</a></ul>
 </ul>";
-        let table_of_contents = generate_table_of_contents(test_input2);
+        let table_of_contents =
+            generate_table_of_contents(test_input2, &GeneretoConfigMarkdown::default());
         assert_eq!(table_of_contents.trim(), expected2.trim());
     }
 
+    #[test]
+    fn test_table_of_contents_skips_metadata_blocks() {
+        let test_input = "---\n# a yaml comment\n---\n\n## Setup {#setup}\n";
+        let markdown = GeneretoConfigMarkdown {
+            metadata_blocks: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            generate_table_of_contents(test_input, &markdown),
+            "<ul class=\"table_of_contents\">\n<li><a href=\"#setup\" class=\"table_of_contents-indent-2\">Setup</a></ul>"
+        );
+        // Without the extension, the block is part of the content.
+        assert!(
+            generate_table_of_contents(test_input, &GeneretoConfigMarkdown::default())
+                .contains("a yaml comment")
+        );
+    }
+
     #[test]
     fn test_remove_after_last_character() {
         assert_eq!(
@@ -509,14 +755,71 @@ mod test {
         \nThis is a test description.";
         // it adds an extra new line at the end, but I don't care
         const EXPECTED: &str = "This is a test description.";
-        assert_eq!(get_description(TEST_INPUT, 100), EXPECTED);
+        assert_eq!(
+            get_description(TEST_INPUT, 100, &GeneretoConfigMarkdown::default()),
+            EXPECTED
+        );
 
         const TEST_INPUT_LINK: &str = "hello world! [how](http://google.com) are you?";
         const EXPECTED_LINK: &str = "hello world! how are you?";
         assert_eq!(
-            get_description(TEST_INPUT_LINK, TEST_INPUT_LINK.len()),
+            get_description(
+                TEST_INPUT_LINK,
+                TEST_INPUT_LINK.len(),
+                &GeneretoConfigMarkdown::default()
+            ),
             EXPECTED_LINK
         );
+
+        const TEST_INPUT_EXTENSIONS: &str =
+            "---\nkey: value\n---\n\nThe text.[^1]\n\n[^1]: The footnote.\n";
+        let markdown = GeneretoConfigMarkdown {
+            metadata_blocks: true,
+            footnotes: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            get_description(TEST_INPUT_EXTENSIONS, 100, &markdown),
+            "The text."
+        );
+
+        // Parsed like the body of the page, whatever the extensions.
+        const TEST_INPUT_BODY: &str = "Some ~~old~~ news.\n";
+        for markdown in [GeneretoConfigMarkdown::default(), markdown] {
+            assert_eq!(
+                get_description(TEST_INPUT_BODY, 100, &markdown),
+                "Some old news."
+            );
+        }
+    }
+
+    #[test]
+    fn test_get_plain_text() {
+        const TEST_INPUT: &str =
+            "## Introduction {#intro}\nSome **bold** text, see [the docs](docs.html).\n\n\
+        <div class=\"note\">html</div>\n\n- `code` item\n- second item\n";
+        assert_eq!(
+            get_plain_text(TEST_INPUT, &GeneretoConfigMarkdown::default()),
+            "Introduction Some bold text, see the docs. code item second item"
+        );
+    }
+
+    #[test]
+    fn test_last_modified_date_without_git_history() {
+        let tmp_dir = tempfile::TempDir::with_prefix("example").unwrap();
+        let file_path = tmp_dir.path().join("page.md");
+        std::fs::write(&file_path, "content").unwrap();
+        // Files outside of a repository fall back to the publish date, even if it's malformed.
+        let published_at = parse_date("2024-01-01T10:30:00+02:00", None);
+        assert_eq!(
+            get_last_modified_date("2024-01-01T10:30:00+02:00", published_at, &file_path),
+            "2024-01-01T10:30:00+02:00"
+        );
+        assert_eq!(get_last_modified_date("", None, &file_path), "");
+        assert_eq!(
+            get_last_modified_date("not a date", None, &file_path),
+            "not a date"
+        );
     }
 
     #[test]
@@ -540,6 +843,7 @@ mod test {
         let mut metadata_raw = PageMetadataRaw {
             title: "Future Post".to_string(),
             publish_date: tomorrow_str,
+            expiry_date: None,
             is_draft: false,
             keywords: "test".to_string(),
             show_table_of_contents: false,
@@ -548,6 +852,9 @@ mod test {
             cover_image: None,
             url: None,
             template_file: None,
+            sitemap: true,
+            priority: None,
+            markdown: None,
             custom_metadata: HashMap::new(),
         };
 
@@ -561,6 +868,8 @@ mod test {
             temp_path,
             "default_cover.jpg",
             "https://fponzi.me",
+            &GeneretoConfigMarkdown::default(),
+            None,
         );
 
         // Check that it's marked as draft
@@ -578,6 +887,8 @@ mod test {
             temp_path,
             "default_cover.jpg",
             "https://fponzi.me",
+            &GeneretoConfigMarkdown::default(),
+            None,
         );
 
         // Check that it's not marked as draft
@@ -591,32 +902,114 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_publish_time() {
+        let page = |publish_date: &str, timezone| {
+            let metadata_raw: PageMetadataRaw =
+                serde_yaml_ng::from_str(&format!("title: Post\npublish_date: '{publish_date}'"))
+                    .unwrap();
+            PageMetadata::new(
+                metadata_raw,
+                "Test content",
+                std::path::Path::new("test_file.md"),
+                "",
+                "https://fponzi.me",
+                &GeneretoConfigMarkdown::default(),
+                timezone,
+            )
+        };
+
+        // Scheduled for later today.
+        let in_an_hour = chrono::Utc::now() + chrono::Duration::hours(1);
+        assert!(page(&in_an_hour.to_rfc3339(), None).is_draft);
+
+        let morning = page("2024-01-15 08:00", Some(chrono_tz::Europe::Rome));
+        assert_eq!(morning.publish_date, "2024-01-15 08:00");
+        assert_eq!(
+            morning.get_variables()[2],
+            (
+                "$GENERETO['publish_datetime']".to_string(),
+                "2024-01-15T08:00:00+01:00".to_string()
+            )
+        );
+        // Sorted newest first, 08:00 in Rome is 07:00 UTC.
+        let evening = page("2024-01-15T18:00:00Z", None);
+        let day = page("2024-01-15", Some(chrono_tz::UTC));
+        let mut pages = [day.clone(), morning.clone(), evening.clone()];
+        pages.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(pages, [evening, morning, day]);
+    }
+
+    #[test]
+    fn test_expiry_date() {
+        let page = |expiry_date: &str| {
+            let metadata_raw: PageMetadataRaw =
+                serde_yaml_ng::from_str(&format!("title: Event\nexpiry_date: '{expiry_date}'"))
+                    .unwrap();
+            PageMetadata::new(
+                metadata_raw,
+                "Test content",
+                std::path::Path::new("test_file.md"),
+                "",
+                "https://fponzi.me",
+                &GeneretoConfigMarkdown::default(),
+                None,
+            )
+        };
+
+        let expired = page("2024-01-15 18:00");
+        assert!(expired.is_draft, "Expired pages should be hidden");
+        assert!(expired.has_expired());
+        assert_eq!(expired.title, "Event");
+        assert!(expired.get_variables().contains(&(
+            "$GENERETO['expiry_date']".to_string(),
+            "2024-01-15 18:00".to_string()
+        )));
+        assert!(!expired.custom_metadata.contains_key("expiry_date"));
+
+        let tomorrow = chrono::Utc::now() + chrono::Duration::days(1);
+        let upcoming = page(&tomorrow.to_rfc3339());
+        assert!(!upcoming.is_draft);
+        assert!(!upcoming.has_expired());
+        // Malformed dates never expire.
+        assert!(!page("next summer").is_draft);
+    }
+
     #[test]
     fn test_custom_metadata() {
         let mut custom_metadata = HashMap::new();
-        custom_metadata.insert("co_authors".to_string(), "John Doe, Jane Smith".to_string());
+        custom_metadata.insert("co_authors".to_string(), "John Doe, Jane Smith".into());
         custom_metadata.insert(
             "project_url".to_string(),
-            "https://github.com/example".to_string(),
+            "https://github.com/example".into(),
         );
 
         let metadata = PageMetadata {
             title: "Test".to_string(),
             publish_date: "2024-01-01".to_string(),
+            published_at: None,
+            expiry_date: String::new(),
+            expires_at: None,
             keywords: "test".to_string(),
             reading_time_mins: "1".to_string(),
             description: "test".to_string(),
             file_name: "test.html".to_string(),
             page_name: "test".to_string(),
+            path: "test.html".to_string(),
             table_of_contents: "".to_string(),
             last_modified_date: "2024-01-01".to_string(),
+            last_modified_at: None,
             cover_image: "test.jpg".to_string(),
             is_draft: false,
             add_title: false,
             article_url: None,
             website_url: "https://fponzi.me".to_string(),
             template_file: None,
+            exclude_from_sitemap: false,
+            sitemap_priority: None,
             custom_metadata,
+            content_html: String::new(),
+            content_text: String::new(),
         };
 
         let variables = metadata.get_variables();
@@ -650,7 +1043,7 @@ project_url: https://github.com/example
         assert_eq!(metadata.title, "Test Page");
         assert_eq!(
             metadata.custom_metadata.get("co_authors"),
-            Some(&"John Doe, Jane Smith".to_string()),
+            Some(&serde_json::Value::from("John Doe, Jane Smith")),
             "Failed to deserialize custom metadata: {:?}",
             metadata.custom_metadata
         );
@@ -660,11 +1053,75 @@ project_url: https://github.com/example
         );
     }
 
+    #[test]
+    fn test_metadata_raw_list_deserialization() {
+        let yaml = r#"
+title: Test Page
+tags: [rust, Static Sites]
+categories: programming, web
+"#;
+        let metadata_raw: PageMetadataRaw = serde_yaml_ng::from_str(yaml).unwrap();
+        assert_eq!(
+            metadata_raw.custom_metadata["tags"],
+            serde_json::json!(["rust", "Static Sites"])
+        );
+
+        let metadata = PageMetadata::new(
+            metadata_raw,
+            "content",
+            std::path::Path::new("test.md"),
+            "",
+            "https://example.com",
+            &GeneretoConfigMarkdown::default(),
+            None,
+        );
+        assert_eq!(metadata.taxonomy_terms("tags"), ["rust", "Static Sites"]);
+        assert_eq!(
+            metadata.taxonomy_terms("categories"),
+            ["programming", "web"]
+        );
+        assert!(metadata.taxonomy_terms("authors").is_empty());
+    }
+
+    #[test]
+    fn test_typed_custom_metadata() {
+        let yaml = r#"
+title: Test Page
+authors: [Ada, Grace]
+series:
+  name: Rust from scratch
+  part: 2
+featured: true
+"#;
+        let metadata_raw: PageMetadataRaw = serde_yaml_ng::from_str(yaml).unwrap();
+        assert_eq!(
+            metadata_raw.custom_metadata["series"],
+            serde_json::json!({"name": "Rust from scratch", "part": 2})
+        );
+
+        let metadata = PageMetadata::new(
+            metadata_raw,
+            "content",
+            std::path::Path::new("test.md"),
+            "",
+            "https://example.com",
+            &GeneretoConfigMarkdown::default(),
+            None,
+        );
+        let template = "$GENERETO['authors'] / $GENERETO['authors.1'] / $GENERETO['series.name'], part $GENERETO['series.part'] / $GENERETO['featured'] / $GENERETO['series']";
+        assert_eq!(
+            metadata.apply(template.to_string()),
+            r#"Ada, Grace / Grace / Rust from scratch, part 2 / true / {"name":"Rust from scratch","part":2}"#
+        );
+        assert_eq!(metadata.taxonomy_terms("authors"), ["Ada", "Grace"]);
+    }
+
     #[test]
     fn test_page_name_derived_from_file_path() {
         let metadata_raw = PageMetadataRaw {
             title: "Test".to_string(),
             publish_date: "2024-01-01".to_string(),
+            expiry_date: None,
             is_draft: false,
             keywords: "".to_string(),
             show_table_of_contents: false,
@@ -673,6 +1130,9 @@ project_url: https://github.com/example
             cover_image: None,
             url: None,
             template_file: None,
+            sitemap: true,
+            priority: None,
+            markdown: None,
             custom_metadata: HashMap::new(),
         };
 
@@ -683,6 +1143,8 @@ project_url: https://github.com/example
             path,
             "default.jpg",
             "https://example.com",
+            &GeneretoConfigMarkdown::default(),
+            None,
         );
 
         assert_eq!(
@@ -731,6 +1193,7 @@ project_url: https://github.com/example
         let raw = PageMetadataRaw {
             title: "Test".to_string(),
             publish_date: "2024-01-01".to_string(),
+            expiry_date: None,
             is_draft: false,
             keywords: String::new(),
             show_table_of_contents: false,
@@ -739,11 +1202,21 @@ project_url: https://github.com/example
             cover_image: None,
             url: Some("https://external-link.com".to_string()),
             template_file: None,
+            sitemap: true,
+            priority: None,
+            markdown: None,
             custom_metadata: HashMap::new(),
         };
         let path = std::path::PathBuf::from("/tmp/test.md");
-        let metadata =
-            PageMetadata::new(raw, "content", &path, "default.jpg", "https://mysite.com");
+        let metadata = PageMetadata::new(
+            raw,
+            "content",
+            &path,
+            "default.jpg",
+            "https://mysite.com",
+            &GeneretoConfigMarkdown::default(),
+            None,
+        );
 
         let variables = metadata.get_variables();
 
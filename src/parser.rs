@@ -1,20 +1,51 @@
-use crate::jinja_processor::{PageContext, SiteContext};
-use crate::page_metadata::{PageMetadata, PageMetadataRaw};
-use crate::DraftsOptions;
+use crate::config::{
+    GeneretoConfigHighlight, GeneretoConfigMarkdown, GeneretoConfigMarkdownOverrides,
+};
+use crate::jinja_processor::{JinjaEnvironment, PageContext};
+use crate::page_metadata::{get_plain_text, PageMetadata, PageMetadataRaw};
+use crate::shortcodes::{process_shortcodes, Shortcodes, SHORTCODES_DIR};
+use crate::{DraftsOptions, GeneretoConfig};
 use anyhow::Context;
+use chrono_tz::Tz;
 use regex::Regex;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Path of `template_filename` in the first of `template_dirs` which has it, so the project
+/// templates override the ones of the themes. If none has it, the path in the first folder
+/// is returned, for the error messages.
+pub fn find_template<P: AsRef<Path>>(template_dirs: &[PathBuf], template_filename: P) -> PathBuf {
+    let template_filename = template_filename.as_ref();
+    template_dirs
+        .iter()
+        .map(|template_dir| template_dir.join(template_filename))
+        .find(|template_path| template_path.exists())
+        .or_else(|| {
+            template_dirs
+                .first()
+                .map(|template_dir| template_dir.join(template_filename))
+        })
+        .unwrap_or_else(|| template_filename.to_path_buf())
+}
 
 /// Process $GENERETO_INCLUDE['filename.html'] directives in templates
 /// This allows including reusable template fragments from the template directory
 pub fn process_includes(template_raw: &str, template_dir: &Path) -> anyhow::Result<String> {
+    process_includes_in(template_raw, &[template_dir.to_path_buf()])
+}
+
+/// Like `process_includes`, looking up the included files in `template_dirs` in order, like
+/// the template folder and then the themes.
+pub fn process_includes_in(
+    template_raw: &str,
+    template_dirs: &[PathBuf],
+) -> anyhow::Result<String> {
     let include_pattern = Regex::new(r"\$GENERETO_INCLUDE\['([^']+)'\]").unwrap();
     let mut result = template_raw.to_string();
 
     for cap in include_pattern.captures_iter(template_raw) {
         let filename = &cap[1];
-        let include_path = template_dir.join(filename);
+        let include_path = find_template(template_dirs, filename);
 
         let include_content = fs::read_to_string(&include_path)
             .with_context(|| format!("Failed to read include file: {}", include_path.display()))?;
@@ -28,10 +59,19 @@ pub fn process_includes(template_raw: &str, template_dir: &Path) -> anyhow::Resu
 /// Load a template file from the template directory and process includes
 /// Returns the processed template content
 pub fn load_template(template_dir: &Path, template_filename: &str) -> anyhow::Result<String> {
-    let template_path = template_dir.join(template_filename);
+    load_template_in(&[template_dir.to_path_buf()], template_filename)
+}
+
+/// Like `load_template`, looking up the template and its includes in `template_dirs` in
+/// order, like the template folder and then the themes.
+pub fn load_template_in(
+    template_dirs: &[PathBuf],
+    template_filename: &str,
+) -> anyhow::Result<String> {
+    let template_path = find_template(template_dirs, template_filename);
     let template_raw = fs::read_to_string(&template_path)
         .with_context(|| format!("Failed to read template file '{}'", template_path.display()))?;
-    process_includes(&template_raw, template_dir)
+    process_includes_in(&template_raw, template_dirs)
 }
 
 pub(crate) const START_PATTERN: &str = "<!-- start_content -->";
@@ -65,14 +105,14 @@ pub fn load_compile_write_with_jinja(
     destination_path: &Path,
     template_raw: &str,
     website_url: &str,
-    site_context: Option<&SiteContext>,
+    jinja: Option<&JinjaEnvironment>,
 ) -> anyhow::Result<Option<PageMetadata>> {
     let (content, metadata) = load_compile_with_jinja(
         default_cover_image,
         entry_path,
         template_raw,
         website_url,
-        site_context,
+        jinja,
     )?;
     if metadata.is_draft && drafts_options.is_hide() {
         return Ok(None);
@@ -103,39 +143,92 @@ pub fn load_compile_with_jinja(
     entry_path: &Path,
     template_raw: &str,
     website_url: &str,
-    site_context: Option<&SiteContext>,
+    jinja: Option<&JinjaEnvironment>,
 ) -> anyhow::Result<(String, PageMetadata)> {
     let source_content = fs::read_to_string(entry_path)?;
     let (intermediate_content, metadata_raw) = compile_page_phase_1(&source_content)?;
     let (content, metadata) = compile_page_phase_2(
         intermediate_content,
         template_raw,
+        "page",
         metadata_raw,
         default_cover_image,
         entry_path,
         website_url,
-        site_context,
+        jinja,
+        &MarkdownOptions::default(),
     )?;
     Ok((content, metadata))
 }
 
+/// Options of the markdown to html compilation. Pages can override the extensions.
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownOptions {
+    /// Highlighting of fenced code blocks. Code is left as it is if None.
+    pub highlight: Option<GeneretoConfigHighlight>,
+    /// Optional markdown extensions.
+    pub extensions: GeneretoConfigMarkdown,
+    /// Folders of the shortcode templates, checked in order. Shortcodes are left as they
+    /// are if empty.
+    pub shortcodes_dirs: Vec<PathBuf>,
+    /// Time zone of the frontmatter dates without an offset. The local one if None.
+    pub timezone: Option<Tz>,
+}
+
+impl MarkdownOptions {
+    pub fn new(genereto_config: &GeneretoConfig) -> Self {
+        Self {
+            highlight: genereto_config.highlight.clone(),
+            extensions: genereto_config.markdown,
+            // Sites without shortcodes keep `{{< >}}` in their text as it is.
+            shortcodes_dirs: genereto_config
+                .template_dirs
+                .iter()
+                .map(|template_dir| template_dir.join(SHORTCODES_DIR))
+                .filter(|shortcodes_dir| shortcodes_dir.is_dir())
+                .collect(),
+            timezone: genereto_config.timezone,
+        }
+    }
+
+    /// The options of a page, with the extensions from its frontmatter applied.
+    pub fn for_page(&self, overrides: Option<&GeneretoConfigMarkdownOverrides>) -> Self {
+        Self {
+            highlight: self.highlight.clone(),
+            extensions: overrides.map_or(self.extensions, |overrides| {
+                self.extensions.with_overrides(overrides)
+            }),
+            shortcodes_dirs: self.shortcodes_dirs.clone(),
+            timezone: self.timezone,
+        }
+    }
+}
+
 /// Compiles the html and applies the metadata substitutions
-/// If site_context is Some, uses Jinja2 template rendering instead of marker-based rendering
+/// If jinja is Some, uses Jinja2 template rendering instead of marker-based rendering.
+/// `template_name` is the file `template_raw` comes from, used in the Jinja2 errors.
+#[allow(clippy::too_many_arguments)]
 pub fn compile_page_phase_2(
     content: String,
     template_raw: &str,
+    template_name: &str,
     metadata_raw: PageMetadataRaw,
     default_cover_image: &str,
     entry_path: &Path,
     website_url: &str,
-    site_context: Option<&SiteContext>,
+    jinja: Option<&JinjaEnvironment>,
+    markdown_options: &MarkdownOptions,
 ) -> anyhow::Result<(String, PageMetadata)> {
-    let metadata = PageMetadata::new(
+    // The description, the ToC and the html are all parsed with the extensions of the page.
+    let markdown_options = markdown_options.for_page(metadata_raw.markdown.as_ref());
+    let mut metadata = PageMetadata::new(
         metadata_raw,
         &content,
         entry_path,
         default_cover_image,
         website_url,
+        &markdown_options.extensions,
+        markdown_options.timezone,
     );
 
     // If add_title is true, add an H1 with the page title at the top of the content
@@ -150,12 +243,33 @@ pub fn compile_page_phase_2(
     let content_with_title =
         content_with_title.replace("$GENERETO['page_name']", &metadata.page_name);
 
-    let html_content = compile_markdown_to_html(&filter_out_comments(&content_with_title));
+    let page_context = match jinja {
+        Some(jinja) => jinja.page_context(&metadata, entry_path),
+        None => PageContext::from_page_metadata(&metadata),
+    };
+    let content_without_comments = filter_out_comments(&content_with_title);
+    let content_without_comments = if markdown_options.shortcodes_dirs.is_empty() {
+        content_without_comments
+    } else {
+        process_shortcodes(
+            &content_without_comments,
+            &Shortcodes {
+                dirs: &markdown_options.shortcodes_dirs,
+                jinja,
+                page: &page_context,
+            },
+        )
+        .with_context(|| format!("Failed to process the shortcodes of {entry_path:?}"))?
+    };
+    metadata.content_text = get_plain_text(&content_without_comments, &markdown_options.extensions);
+    let html_content = compile_markdown_to_html(&content_without_comments, &markdown_options);
 
-    let final_page = if let Some(site) = site_context {
+    let final_page = if let Some(jinja) = jinja {
         // Use Jinja2 template rendering
-        let page_context = PageContext::from_page_metadata(&metadata);
-        crate::jinja_processor::render_page(template_raw, site, &page_context, &html_content)?
+        let final_page =
+            jinja.render_page(template_name, template_raw, &page_context, &html_content)?;
+        metadata.content_html = html_content;
+        final_page
     } else {
         // Use traditional marker-based rendering
         let mut final_page = template_raw.to_string();
@@ -171,30 +285,64 @@ pub fn compile_page_phase_2(
 
         final_page.replace_range(start..end + END_PATTERN.len(), &html_content);
         let final_page = metadata.apply(final_page);
+        metadata.content_html = metadata.apply(html_content).replace("&amp;#36;", "$");
         final_page.replace("&amp;#36;", "$")
     };
 
     Ok((final_page, metadata))
 }
 
+/// Reads the metadata of the page at `entry_path`, without compiling it.
+pub(crate) fn load_page_metadata(
+    entry_path: &Path,
+    default_cover_image: &str,
+    website_url: &str,
+    markdown_options: &MarkdownOptions,
+) -> anyhow::Result<PageMetadata> {
+    let source_content = fs::read_to_string(entry_path)
+        .with_context(|| format!("Failed to read page {entry_path:?}"))?;
+    let (content, metadata_raw) = compile_page_phase_1(&source_content)
+        .with_context(|| format!("Failed to parse page {entry_path:?}"))?;
+    let markdown_options = markdown_options.for_page(metadata_raw.markdown.as_ref());
+    Ok(PageMetadata::new(
+        metadata_raw,
+        &content,
+        entry_path,
+        default_cover_image,
+        website_url,
+        &markdown_options.extensions,
+        markdown_options.timezone,
+    ))
+}
+
 /// Split the source content into the metadata and the content
 /// This function is public so that callers can parse metadata first to check for template_file
+///
+/// The metadata is YAML between `---` lines, TOML between `+++` lines or a JSON object at the
+/// top of the page. Pages without the opening `---` have the legacy format: YAML, then `---`.
 pub fn compile_page_phase_1(source_content: &str) -> anyhow::Result<(String, PageMetadataRaw)> {
     let trimmed_content = source_content.trim_start();
 
-    if trimmed_content.starts_with("---") {
+    let (metadata, content) = if trimmed_content.starts_with("+++") {
+        let (metadata, content) = split_frontmatter(trimmed_content, r"\+\+\++\n")?;
+        (parse_toml_metadata(metadata)?, content)
+    } else if trimmed_content.starts_with('{') {
+        let mut stream = serde_json::Deserializer::from_str(trimmed_content).into_iter();
+        let metadata: PageMetadataRaw = stream
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Failed to find metadata in page"))?
+            .context("Failed to deserialize the JSON frontmatter")?;
+        // Like with the fences, the content starts on the line after the metadata.
+        let rest = &trimmed_content[stream.byte_offset()..];
+        let content = rest
+            .trim_start_matches([' ', '\t'])
+            .strip_prefix('\n')
+            .unwrap_or(rest);
+        (metadata, content)
+    } else if trimmed_content.starts_with("---") {
         // Standard frontmatter format: ---\nmetadata\n---\ncontent
-        let pattern = Regex::new(r"---+\n").unwrap();
-        let mut fields: Vec<&str> = pattern.splitn(trimmed_content, 3).collect();
-        if fields.len() < 3 {
-            return Err(anyhow::anyhow!("Failed to find metadata in page"));
-        }
-        let (_, metadata, content) = (fields.remove(0), fields.remove(0), fields.remove(0));
-        let content = add_ids_to_headings(content);
-
-        let metadata: PageMetadataRaw = serde_yaml_ng::from_str(metadata)
-            .context(format!("Failed to deserialize metadata, did you remember to put the metadata section? Metadata: '{}'", metadata))?;
-        Ok((content, metadata))
+        let (metadata, content) = split_frontmatter(trimmed_content, r"---+\n")?;
+        (parse_yaml_metadata(metadata)?, content)
     } else {
         // Legacy format: metadata\n---\ncontent
         let pattern = Regex::new(r"---+\n").unwrap();
@@ -203,26 +351,96 @@ pub fn compile_page_phase_1(source_content: &str) -> anyhow::Result<(String, Pag
             return Err(anyhow::anyhow!("Failed to find metadata in page"));
         }
         let (metadata, content) = (fields.remove(0), fields.remove(0));
-        let content = add_ids_to_headings(content);
+        (parse_yaml_metadata(metadata)?, content)
+    };
+    Ok((add_ids_to_headings(content), metadata))
+}
 
-        let metadata: PageMetadataRaw = serde_yaml_ng::from_str(metadata)
-            .context(format!("Failed to deserialize metadata, did you remember to put the metadata section? Metadata: '{}'", metadata))?;
-        Ok((content, metadata))
+/// Splits the metadata between the first two `fence` lines from the content after them.
+fn split_frontmatter<'a>(
+    source_content: &'a str,
+    fence: &str,
+) -> anyhow::Result<(&'a str, &'a str)> {
+    let pattern = Regex::new(fence).unwrap();
+    let mut fields: Vec<&str> = pattern.splitn(source_content, 3).collect();
+    if fields.len() < 3 {
+        return Err(anyhow::anyhow!("Failed to find metadata in page"));
     }
+    Ok((fields.remove(1), fields.remove(1)))
+}
+
+fn parse_yaml_metadata(metadata: &str) -> anyhow::Result<PageMetadataRaw> {
+    serde_yaml_ng::from_str(metadata)
+        .context(format!("Failed to deserialize metadata, did you remember to put the metadata section? Metadata: '{}'", metadata))
 }
 
-pub(crate) fn compile_markdown_to_html(markdown_input: &str) -> String {
-    use pulldown_cmark::{html, Options, Parser};
+/// TOML dates, like `publish_date = 2024-01-15`, are read as strings.
+fn parse_toml_metadata(metadata: &str) -> anyhow::Result<PageMetadataRaw> {
+    fn to_json(value: toml::Value) -> serde_json::Value {
+        match value {
+            toml::Value::String(value) => value.into(),
+            toml::Value::Integer(value) => value.into(),
+            toml::Value::Float(value) => value.into(),
+            toml::Value::Boolean(value) => value.into(),
+            toml::Value::Datetime(value) => value.to_string().into(),
+            toml::Value::Array(values) => values.into_iter().map(to_json).collect(),
+            toml::Value::Table(table) => serde_json::Value::Object(
+                table
+                    .into_iter()
+                    .map(|(key, value)| (key, to_json(value)))
+                    .collect(),
+            ),
+        }
+    }
+    let table: toml::Table =
+        toml::from_str(metadata).context("Failed to deserialize the TOML frontmatter")?;
+    serde_json::from_value(to_json(toml::Value::Table(table)))
+        .context("Failed to deserialize the TOML frontmatter")
+}
+
+/// Markdown extensions enabled when parsing pages.
+pub(crate) fn markdown_extensions(extensions: &GeneretoConfigMarkdown) -> pulldown_cmark::Options {
+    use pulldown_cmark::Options;
     let mut options = Options::empty();
     options.insert(Options::ENABLE_STRIKETHROUGH);
     options.insert(Options::ENABLE_HEADING_ATTRIBUTES);
     options.insert(Options::ENABLE_TABLES);
+    options.set(Options::ENABLE_FOOTNOTES, extensions.footnotes);
+    options.set(Options::ENABLE_TASKLISTS, extensions.tasklists);
+    options.set(
+        Options::ENABLE_SMART_PUNCTUATION,
+        extensions.smart_punctuation,
+    );
+    options.set(Options::ENABLE_DEFINITION_LIST, extensions.definition_lists);
+    options.set(Options::ENABLE_MATH, extensions.math);
+    options.set(Options::ENABLE_GFM, extensions.gfm);
+    options.set(
+        Options::ENABLE_YAML_STYLE_METADATA_BLOCKS
+            | Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS,
+        extensions.metadata_blocks,
+    );
+    options
+}
 
-    let parser = Parser::new_ext(markdown_input, options);
+pub(crate) fn compile_markdown_to_html(
+    markdown_input: &str,
+    markdown_options: &MarkdownOptions,
+) -> String {
+    use pulldown_cmark::{html, Parser};
+    let parser = Parser::new_ext(
+        markdown_input,
+        markdown_extensions(&markdown_options.extensions),
+    );
 
     // Write to String buffer.
     let mut html_output = String::new();
-    html::push_html(&mut html_output, parser);
+    match &markdown_options.highlight {
+        Some(highlight) => html::push_html(
+            &mut html_output,
+            crate::highlighting::highlight_code_blocks(parser, highlight).into_iter(),
+        ),
+        None => html::push_html(&mut html_output, parser),
+    }
     html_output
 }
 
@@ -314,6 +532,28 @@ some code!!
         );
     }
 
+    #[test]
+    fn test_find_template() -> anyhow::Result<()> {
+        use std::fs;
+        use std::path::PathBuf;
+        let project = tempfile::tempdir()?;
+        let theme = tempfile::tempdir()?;
+        fs::write(project.path().join("footer.html"), "project")?;
+        fs::write(theme.path().join("footer.html"), "theme")?;
+        fs::write(theme.path().join("index.html"), "theme")?;
+        let template_dirs = [project.path().to_path_buf(), theme.path().to_path_buf()];
+
+        let find = |name: &str| super::find_template(&template_dirs, name);
+        assert_eq!(find("footer.html"), project.path().join("footer.html"));
+        assert_eq!(find("index.html"), theme.path().join("index.html"));
+        assert_eq!(find("missing.html"), project.path().join("missing.html"));
+        assert_eq!(
+            super::find_template(&[] as &[PathBuf], "index.html"),
+            PathBuf::from("index.html")
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_add_ids_to_headings() {
         let page_content = r#"# heading1
@@ -380,6 +620,34 @@ some code!!
         assert_eq!(metadata.title, "Test Page");
     }
 
+    #[test]
+    fn test_compile_page_phase_1_toml_and_json_frontmatter() {
+        let yaml = "---\ntitle: Test Page\npublish_date: 2024-01-15\ntags: [rust, web]\nseries:\n  part: 2\n---\n\n# Content\n";
+        let toml = "+++\ntitle = \"Test Page\"\npublish_date = 2024-01-15\ntags = [\"rust\", \"web\"]\n\n[series]\npart = 2\n+++\n\n# Content\n";
+        let json = "{\n  \"title\": \"Test Page\",\n  \"publish_date\": \"2024-01-15\",\n  \"tags\": [\"rust\", \"web\"],\n  \"series\": {\"part\": 2}\n}\n\n# Content\n";
+
+        let (yaml_content, yaml_metadata) = super::compile_page_phase_1(yaml).unwrap();
+        for source_content in [toml, json] {
+            let (content, metadata) = super::compile_page_phase_1(source_content).unwrap();
+            assert_eq!(content, yaml_content);
+            assert_eq!(
+                serde_json::to_value(&metadata).unwrap(),
+                serde_json::to_value(&yaml_metadata).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_compile_page_phase_1_errors_point_at_the_line() {
+        let toml = "+++\ntitle = \"Test Page\"\ntags = [rust]\n+++\n\nContent";
+        let error = format!("{:#}", super::compile_page_phase_1(toml).unwrap_err());
+        assert!(error.contains("line 2"), "got: {error}");
+
+        let json = "{\n  \"title\": \"Test Page\",\n  \"tags\": [rust]\n}\n\nContent";
+        let error = format!("{:#}", super::compile_page_phase_1(json).unwrap_err());
+        assert!(error.contains("line 3"), "got: {error}");
+    }
+
     #[test]
     fn test_page_name_substituted_in_markdown_image_refs() {
         use crate::page_metadata::PageMetadataRaw;
@@ -391,6 +659,7 @@ some code!!
         let metadata_raw = PageMetadataRaw {
             title: "Test".to_string(),
             publish_date: "2024-01-01".to_string(),
+            expiry_date: None,
             is_draft: false,
             keywords: "".to_string(),
             show_table_of_contents: false,
@@ -399,6 +668,9 @@ some code!!
             cover_image: None,
             url: None,
             template_file: None,
+            sitemap: true,
+            priority: None,
+            markdown: None,
             custom_metadata: HashMap::new(),
         };
         let entry_path = Path::new("2026-03-29-a-pretty-printer-for-tlaplus.md");
@@ -406,11 +678,13 @@ some code!!
         let (html, _metadata) = super::compile_page_phase_2(
             content,
             template,
+            "index.html",
             metadata_raw,
             "default.jpg",
             entry_path,
             "https://example.com",
             None,
+            &super::MarkdownOptions::default(),
         )
         .unwrap();
 
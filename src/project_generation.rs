@@ -4,7 +4,28 @@ use std::path::Path;
 const SAMPLE_BLOG_PAGE: &str = include_str!("../sample-genereto-project/templates/main/blog.html");
 const SAMPLE_INDEX_PAGE: &str =
     include_str!("../sample-genereto-project/templates/main/index.html");
+const SAMPLE_HEADER: &str = include_str!("../sample-genereto-project/templates/main/header.html");
+const SAMPLE_FOOTER: &str = include_str!("../sample-genereto-project/templates/main/footer.html");
+const SAMPLE_STYLES: &str =
+    include_str!("../sample-genereto-project/templates/main/res/styles.css");
+const SAMPLE_SEARCH_SCRIPT: &str =
+    include_str!("../sample-genereto-project/templates/main/res/search.js");
+const SAMPLE_SHORTCODES: [(&str, &str); 3] = [
+    (
+        "figure.html",
+        include_str!("../sample-genereto-project/templates/main/shortcodes/figure.html"),
+    ),
+    (
+        "note.html",
+        include_str!("../sample-genereto-project/templates/main/shortcodes/note.html"),
+    ),
+    (
+        "youtube.html",
+        include_str!("../sample-genereto-project/templates/main/shortcodes/youtube.html"),
+    ),
+];
 const SAMPLE_CONFIG_FILE: &str = include_str!("../sample-genereto-project/config.yml");
+const SAMPLE_POST_ARCHETYPE: &str = include_str!("../sample-genereto-project/archetypes/post.md");
 const SAMPLE_CONTENT_PAGE: &str =
     include_str!("../sample-genereto-project/content/blog/2024-05-04-hello-world.md");
 const SAMPLE_IMAGE: &[u8] =
@@ -18,6 +39,7 @@ pub fn generate_project(project_path: &Path, override_git: bool) -> anyhow::Resu
     }
     // create directories:
     std::fs::create_dir_all(project_path.join("genereto-project/templates/main/res"))?;
+    std::fs::create_dir_all(project_path.join("genereto-project/templates/main/shortcodes"))?;
     //start writing files to where they belong:
     std::fs::write(
         project_path.join("genereto-project/templates/main/blog.html"),
@@ -27,10 +49,39 @@ pub fn generate_project(project_path: &Path, override_git: bool) -> anyhow::Resu
         project_path.join("genereto-project/templates/main/index.html"),
         SAMPLE_INDEX_PAGE,
     )?;
+    std::fs::write(
+        project_path.join("genereto-project/templates/main/header.html"),
+        SAMPLE_HEADER,
+    )?;
+    std::fs::write(
+        project_path.join("genereto-project/templates/main/footer.html"),
+        SAMPLE_FOOTER,
+    )?;
+    std::fs::write(
+        project_path.join("genereto-project/templates/main/res/styles.css"),
+        SAMPLE_STYLES,
+    )?;
+    std::fs::write(
+        project_path.join("genereto-project/templates/main/res/search.js"),
+        SAMPLE_SEARCH_SCRIPT,
+    )?;
+    for (file_name, shortcode) in SAMPLE_SHORTCODES {
+        std::fs::write(
+            project_path.join(format!(
+                "genereto-project/templates/main/shortcodes/{file_name}"
+            )),
+            shortcode,
+        )?;
+    }
     std::fs::write(
         project_path.join("genereto-project/config.yml"),
         SAMPLE_CONFIG_FILE,
     )?;
+    std::fs::create_dir_all(project_path.join("genereto-project/archetypes"))?;
+    std::fs::write(
+        project_path.join("genereto-project/archetypes/post.md"),
+        SAMPLE_POST_ARCHETYPE,
+    )?;
     // get todays date in the format YYYY-MM-DD
     let today = chrono::offset::Local::now().format("%Y-%m-%d").to_string();
     // create a directory for today's blog post
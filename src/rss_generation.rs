@@ -1,62 +1,271 @@
+use crate::fs_util::{mime_type, write_if_changed};
 use crate::page_metadata::PageMetadata;
+use crate::GeneretoConfig;
 use anyhow::Result;
-use chrono::NaiveDate;
-use rss::{ChannelBuilder, ItemBuilder};
-use std::path::Path;
+use rss::{CategoryBuilder, ChannelBuilder, EnclosureBuilder, GuidBuilder, ItemBuilder};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Channel level information of a feed, shared by all the feed formats.
+pub(crate) struct FeedInfo<'a> {
+    pub(crate) title: String,
+    pub(crate) url: &'a str,
+    pub(crate) description: &'a str,
+    pub(crate) language: &'a str,
+    /// Author of the articles which don't set their own `author` metadata.
+    pub(crate) author: Option<&'a str>,
+    /// If true, items carry the whole rendered article.
+    pub(crate) full_content: bool,
+    /// Where cover images are looked up, to read their size.
+    pub(crate) output_dir: &'a Path,
+    /// If true, RSS items are linked by file name, like the blog feed always did, so feed
+    /// readers don't show its articles again as new ones.
+    pub(crate) link_by_file_name: bool,
+}
+
+impl<'a> FeedInfo<'a> {
+    pub(crate) fn new(genereto_config: &'a GeneretoConfig) -> Self {
+        Self {
+            title: genereto_config.title.clone(),
+            url: &genereto_config.url,
+            description: &genereto_config.description,
+            language: &genereto_config.language,
+            author: genereto_config.author.as_deref(),
+            full_content: genereto_config.feed_full_content,
+            output_dir: &genereto_config.output_dir_path,
+            link_by_file_name: true,
+        }
+    }
+}
 
 pub fn generate_rss(
-    website_title: &str,
-    url: &str,
-    description: &str,
+    feed: &FeedInfo,
     metadatas: Vec<PageMetadata>,
-    output_dir: &Path,
+    output_path: &Path,
 ) -> Result<()> {
     let channel = ChannelBuilder::default()
-        .title(website_title)
-        .link(url)
-        .description(description)
-        .language("en-us".to_string())
-        .items(articles_to_items(url, metadatas))
+        .title(feed.title.as_str())
+        .link(feed.url)
+        .description(feed.description)
+        .language(feed.language.to_string())
+        .items(articles_to_items(feed, metadatas))
         .build();
 
     let rss = channel.to_string();
-    std::fs::write(output_dir.join("rss.xml"), rss)?;
+    write_if_changed(output_path, rss)?;
     Ok(())
 }
-fn articles_to_items(url: &str, metadatas: Vec<PageMetadata>) -> Vec<rss::Item> {
+fn articles_to_items(feed: &FeedInfo, metadatas: Vec<PageMetadata>) -> Vec<rss::Item> {
     metadatas
         .into_iter()
-        .filter(|md| !(md.is_draft || md.file_name == "error.html"))
+        .filter(is_feed_article)
         .map(|md| {
+            let link = if feed.link_by_file_name {
+                format!("{}/{}", feed.url, md.file_name)
+            } else {
+                article_link(feed.url, &md)
+            };
+            let enclosure = cover_image_file(feed, &md).map(|(url, file)| {
+                let length = fs::metadata(&file).map(|m| m.len()).unwrap_or_default();
+                EnclosureBuilder::default()
+                    .url(url)
+                    .length(length.to_string())
+                    .mime_type(mime_type(&file))
+                    .build()
+            });
+            let categories = keywords(&md)
+                .map(|keyword| CategoryBuilder::default().name(keyword).build())
+                .collect::<Vec<_>>();
             ItemBuilder::default()
+                .guid(
+                    GuidBuilder::default()
+                        .value(link.as_str())
+                        .permalink(true)
+                        .build(),
+                )
+                .link(link)
+                .author(article_author(feed, &md))
+                .categories(categories)
+                .enclosure(enclosure)
+                .content(article_content(feed, &md))
+                .pub_date(get_complaint_date(&md))
                 .title(md.title)
-                .link(format!("{}/{}", url, md.file_name))
                 .description(md.description)
-                .pub_date(get_complaint_date(&md.publish_date))
                 .build()
         })
         .collect()
 }
 
+/// Drafts and the error page never show up in feeds.
+pub(crate) fn is_feed_article(md: &PageMetadata) -> bool {
+    !(md.is_draft || md.file_name == "error.html")
+}
+
+/// Absolute link of an article. Pages with their own output are linked by path, as they can
+/// be in any folder.
+pub(crate) fn article_link(url: &str, md: &PageMetadata) -> String {
+    let link = if md.path.is_empty() {
+        &md.file_name
+    } else {
+        &md.path
+    };
+    format!("{}/{}", url, link)
+}
+
+/// The `author` metadata of the article, or the site author.
+pub(crate) fn article_author(feed: &FeedInfo, md: &PageMetadata) -> Option<String> {
+    md.custom_text("author")
+        .or_else(|| feed.author.map(str::to_string))
+}
+
+/// The rendered article, if the feed carries the full content.
+pub(crate) fn article_content(feed: &FeedInfo, md: &PageMetadata) -> Option<String> {
+    (feed.full_content && !md.content_html.is_empty()).then(|| md.content_html.clone())
+}
+
+/// Comma separated keywords of the article, used as feed categories.
+pub(crate) fn keywords(md: &PageMetadata) -> impl Iterator<Item = &str> {
+    md.keywords
+        .split(',')
+        .map(str::trim)
+        .filter(|keyword| !keyword.is_empty())
+}
+
+/// Absolute url and file in the output folder of the cover image of the article.
+/// Cover images are relative to the article folder, or to the output folder for the default
+/// cover image. Returns None for remote images and images which can't be found.
+pub(crate) fn cover_image_file(feed: &FeedInfo, md: &PageMetadata) -> Option<(String, PathBuf)> {
+    let cover_image = md.cover_image.as_str();
+    if cover_image.is_empty() || cover_image.starts_with("http") {
+        return None;
+    }
+    let article_dir = md.path.rsplit_once('/').map(|(dir, _)| dir);
+    let candidates = article_dir
+        .map(|dir| format!("{dir}/{cover_image}"))
+        .into_iter()
+        .chain([cover_image.to_string()]);
+    for relative_path in candidates {
+        let file = feed.output_dir.join(&relative_path);
+        if file.is_file() {
+            return Some((format!("{}/{relative_path}", feed.url), file));
+        }
+    }
+    debug!("Cover image {cover_image} of {} not found.", md.file_name);
+    None
+}
+
 /// Returns RFC-822 date format
-fn get_complaint_date(date: &str) -> String {
-    // takes date as "2018-08-06" and returns "Mon, 06 Aug 2018 00:00:00 UTC"
-    if let Ok(parsed_date) = NaiveDate::parse_from_str(date, "%Y-%m-%d") {
-        parsed_date.format("%a, %d %b %Y 00:00:00 UTC").to_string()
+fn get_complaint_date(md: &PageMetadata) -> String {
+    // takes the publish date 2018-08-06T10:30:00+02:00 and returns "Mon, 06 Aug 2018 10:30:00 +0200"
+    if let Some(published_at) = md.published_at {
+        published_at.format("%a, %d %b %Y %H:%M:%S %z").to_string()
     } else {
-        error!("Error parsing date: {}, will use it as date string.", date);
-        date.to_string()
+        error!(
+            "Error parsing date: {}, will use it as date string.",
+            md.publish_date
+        );
+        md.publish_date.clone()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::dates::parse_date;
+    use chrono_tz::Tz;
+    use tempfile::TempDir;
+
+    fn article(publish_date: &str) -> PageMetadata {
+        PageMetadata {
+            publish_date: publish_date.to_string(),
+            published_at: parse_date(publish_date, Some(Tz::UTC)),
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn test_get_complaint_date() {
         assert_eq!(
-            get_complaint_date("2018-08-06"),
-            "Mon, 06 Aug 2018 00:00:00 UTC"
+            get_complaint_date(&article("2018-08-06")),
+            "Mon, 06 Aug 2018 00:00:00 +0000"
+        );
+        assert_eq!(
+            get_complaint_date(&article("2018-08-06T10:30:00+02:00")),
+            "Mon, 06 Aug 2018 10:30:00 +0200"
+        );
+        assert_eq!(get_complaint_date(&article("someday")), "someday");
+    }
+
+    #[test]
+    fn test_articles_to_items() -> std::io::Result<()> {
+        let tmp_dir = TempDir::with_prefix("rss")?;
+        let output_dir = tmp_dir.path();
+        fs::create_dir_all(output_dir.join("blog/first-post"))?;
+        fs::write(output_dir.join("blog/first-post/cover.png"), "12345")?;
+        let feed = FeedInfo {
+            title: "Site".to_string(),
+            url: "https://example.com",
+            description: "A site",
+            language: "it",
+            author: Some("site@example.com (Site Author)"),
+            full_content: true,
+            output_dir,
+            link_by_file_name: false,
+        };
+        let article = PageMetadata {
+            title: "First Post".to_string(),
+            publish_date: "2024-01-01".to_string(),
+            keywords: "rust, web".to_string(),
+            file_name: "first-post.html".to_string(),
+            path: "blog/first-post.html".to_string(),
+            cover_image: "first-post/cover.png".to_string(),
+            content_html: "<p>Hello</p>".to_string(),
+            custom_metadata: [("author".to_string(), "me@example.com".into())].into(),
+            ..Default::default()
+        };
+        let no_cover = PageMetadata {
+            cover_image: "missing.png".to_string(),
+            custom_metadata: Default::default(),
+            ..article.clone()
+        };
+
+        let items = articles_to_items(&feed, vec![article, no_cover.clone()]);
+        let item = &items[0];
+        let guid = item.guid.as_ref().unwrap();
+        assert_eq!(guid.value, "https://example.com/blog/first-post.html");
+        assert!(guid.permalink);
+        assert_eq!(item.content.as_deref(), Some("<p>Hello</p>"));
+        assert_eq!(item.author.as_deref(), Some("me@example.com"));
+        let categories: Vec<&str> = item.categories.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(categories, ["rust", "web"]);
+        let enclosure = item.enclosure.as_ref().unwrap();
+        assert_eq!(
+            enclosure.url,
+            "https://example.com/blog/first-post/cover.png"
+        );
+        assert_eq!(enclosure.length, "5");
+        assert_eq!(enclosure.mime_type, "image/png");
+
+        assert!(items[1].enclosure.is_none());
+        assert_eq!(
+            items[1].author.as_deref(),
+            Some("site@example.com (Site Author)")
+        );
+
+        // The blog feed keeps its links and guids by file name.
+        let feed = FeedInfo {
+            link_by_file_name: true,
+            ..feed
+        };
+        let items = articles_to_items(&feed, vec![no_cover]);
+        assert_eq!(
+            items[0].link.as_deref(),
+            Some("https://example.com/first-post.html")
+        );
+        assert_eq!(
+            items[0].guid.as_ref().unwrap().value,
+            "https://example.com/first-post.html"
         );
+        Ok(())
     }
 }
@@ -0,0 +1,84 @@
+use crate::build_cache::BuildCache;
+use crate::fs_util::write_if_changed;
+use crate::page_metadata::PageMetadata;
+use crate::{DraftsOptions, GeneretoConfig};
+use anyhow::Context;
+use serde::Serialize;
+
+const SEARCH_INDEX_FILENAME: &str = "search-index.json";
+
+/// A page of the search index. The url is relative to the output folder, so the index works
+/// wherever the website is served from.
+#[derive(Debug, Serialize)]
+struct SearchEntry<'a> {
+    title: &'a str,
+    url: &'a str,
+    description: &'a str,
+    keywords: &'a str,
+    body: &'a str,
+}
+
+/// Writes search-index.json with the published pages and blog articles, for client side search.
+pub(crate) fn generate_search_index(
+    genereto_config: &GeneretoConfig,
+    drafts_options: &DraftsOptions,
+    pages: &[&PageMetadata],
+    build_cache: &mut BuildCache,
+) -> anyhow::Result<()> {
+    if !genereto_config.search_index {
+        return Ok(());
+    }
+    let mut entries: Vec<SearchEntry> = pages
+        .iter()
+        .filter(|page| is_searchable(page, drafts_options))
+        .map(|page| SearchEntry {
+            title: &page.title,
+            url: &page.path,
+            description: &page.description,
+            keywords: &page.keywords,
+            body: &page.content_text,
+        })
+        .collect();
+    entries.sort_by(|a, b| a.url.cmp(b.url));
+
+    let index_path = genereto_config.output_dir_path.join(SEARCH_INDEX_FILENAME);
+    write_if_changed(&index_path, serde_json::to_string(&entries)?)
+        .with_context(|| format!("Failed to write {index_path:?}"))?;
+    build_cache.record_output(&index_path);
+    Ok(())
+}
+
+/// Pages without an output of their own, like blog.yml entries, can't be found.
+fn is_searchable(page: &PageMetadata, drafts_options: &DraftsOptions) -> bool {
+    !page.path.is_empty()
+        && page.file_name != "error.html"
+        && (!page.is_draft || drafts_options.is_dev())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_searchable() {
+        let page = PageMetadata {
+            file_name: "about.html".to_string(),
+            path: "about.html".to_string(),
+            ..Default::default()
+        };
+        assert!(is_searchable(&page, &DraftsOptions::Build));
+
+        let draft = PageMetadata {
+            is_draft: true,
+            ..page.clone()
+        };
+        assert!(!is_searchable(&draft, &DraftsOptions::Build));
+        assert!(is_searchable(&draft, &DraftsOptions::Dev));
+
+        let yaml_entry = PageMetadata {
+            path: String::new(),
+            ..page.clone()
+        };
+        assert!(!is_searchable(&yaml_entry, &DraftsOptions::Build));
+    }
+}
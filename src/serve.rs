@@ -0,0 +1,315 @@
+use crate::fs_util::mime_type;
+use crate::{build, DraftsOptions, GeneretoConfig};
+use anyhow::Context;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+use std::{fs, thread};
+
+const LIVE_RELOAD_PATH: &str = "/__genereto/livereload";
+const LIVE_RELOAD_SCRIPT: &str = r#"<script>new EventSource("/__genereto/livereload").addEventListener("reload", function () { location.reload(); });</script>"#;
+// How often an idle live reload connection gets a keep-alive comment. Also used to notice
+// browsers that went away.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Tracks the number of completed builds, so connected browsers can be told to reload.
+#[derive(Debug, Default)]
+pub struct LiveReload {
+    generation: Mutex<u64>,
+    changed: Condvar,
+}
+
+impl LiveReload {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Signals every connected browser that a new build is available.
+    pub fn notify(&self) {
+        let mut generation = self.generation.lock().unwrap();
+        *generation += 1;
+        self.changed.notify_all();
+    }
+
+    pub fn generation(&self) -> u64 {
+        *self.generation.lock().unwrap()
+    }
+
+    /// Blocks until the generation moves past `seen` or the timeout expires.
+    /// Returns the current generation.
+    fn wait_for_change(&self, seen: u64, timeout: Duration) -> u64 {
+        let generation = self.generation.lock().unwrap();
+        let (generation, _) = self
+            .changed
+            .wait_timeout_while(generation, timeout, |generation| *generation == seen)
+            .unwrap();
+        *generation
+    }
+}
+
+/// Builds the project, serves it on localhost and rebuilds it whenever content, templates or
+/// config change. Open browsers are reloaded after every rebuild. `load_config` is called
+/// again before every rebuild.
+///
+/// The preview points at the local server and can include drafts, so it is built in a folder
+/// of its own, leaving the output folder as it is. Build errors are logged, and the server
+/// keeps going.
+pub fn run<L>(load_config: L, drafts_options: DraftsOptions, port: u16) -> anyhow::Result<()>
+where
+    L: Fn() -> anyhow::Result<GeneretoConfig>,
+{
+    let address = format!("127.0.0.1:{port}");
+    let preview_dir = std::env::temp_dir().join(format!("genereto-serve-{port}"));
+    let load_config = || -> anyhow::Result<GeneretoConfig> {
+        let mut config = load_config()?.with_output_dir(&preview_dir)?;
+        // Point $GENERETO['url'] at the local server, so absolute links and assets resolve.
+        config.url = format!("http://{address}");
+        Ok(config)
+    };
+
+    let config = load_config()?;
+    if let Err(e) = build(&config, &drafts_options) {
+        error!("Build failed, fix it to get a new build: {e:?}");
+    }
+
+    let listener =
+        TcpListener::bind(&address).with_context(|| format!("Failed to bind to {address}"))?;
+    let live_reload = Arc::new(LiveReload::new());
+    {
+        let root = config.output_dir_path.clone();
+        let live_reload = live_reload.clone();
+        thread::spawn(move || serve(listener, root, live_reload));
+    }
+    println!(
+        "Serving {} at http://{address}",
+        config.output_dir_path.display()
+    );
+
+    crate::watch::watch(config, load_config, &drafts_options, |_| {
+        live_reload.notify()
+    })
+}
+
+/// Accepts connections forever, serving files from `root`.
+pub fn serve(listener: TcpListener, root: PathBuf, live_reload: Arc<LiveReload>) {
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                warn!("Failed to accept connection: {e}");
+                continue;
+            }
+        };
+        let root = root.clone();
+        let live_reload = live_reload.clone();
+        thread::spawn(move || {
+            if let Err(e) = handle_connection(stream, &root, &live_reload) {
+                debug!("Connection closed: {e}");
+            }
+        });
+    }
+}
+
+fn handle_connection(stream: TcpStream, root: &Path, live_reload: &LiveReload) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Drain the headers, we don't need any of them.
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let target = parts.next().unwrap_or("/");
+    let mut stream = stream;
+    if method != "GET" && method != "HEAD" {
+        return write_response(&mut stream, "405 Method Not Allowed", "text/plain", b"");
+    }
+
+    let url_path = target.split(['?', '#']).next().unwrap_or("/");
+    if url_path == LIVE_RELOAD_PATH {
+        return stream_live_reload(stream, live_reload);
+    }
+
+    let Some(file_path) = resolve_path(root, url_path) else {
+        return write_response(&mut stream, "404 Not Found", "text/plain", b"Not Found");
+    };
+    let body = fs::read(&file_path)?;
+    let content_type = mime_type(&file_path);
+    let body = if content_type.starts_with("text/html") {
+        inject_live_reload(&String::from_utf8_lossy(&body)).into_bytes()
+    } else {
+        body
+    };
+    let body = if method == "HEAD" { &[][..] } else { &body[..] };
+    write_response(&mut stream, "200 OK", content_type, body)
+}
+
+fn write_response(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()
+}
+
+/// Keeps a server-sent events stream open, sending a `reload` event after every build.
+fn stream_live_reload(mut stream: TcpStream, live_reload: &LiveReload) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-store\r\nConnection: keep-alive\r\n\r\n"
+    )?;
+    stream.flush()?;
+    let mut seen = live_reload.generation();
+    loop {
+        let generation = live_reload.wait_for_change(seen, KEEP_ALIVE_INTERVAL);
+        if generation == seen {
+            // A failing write means the browser went away, which ends this thread.
+            stream.write_all(b": keep-alive\n\n")?;
+        } else {
+            seen = generation;
+            write!(stream, "event: reload\ndata: {generation}\n\n")?;
+        }
+        stream.flush()?;
+    }
+}
+
+/// Maps an url path to a file inside `root`. Directories resolve to their index.html, and
+/// extension-less paths fall back to the ".html" file with the same name.
+fn resolve_path(root: &Path, url_path: &str) -> Option<PathBuf> {
+    let decoded = percent_decode(url_path);
+    let mut path = root.to_path_buf();
+    for segment in decoded.split('/') {
+        match segment {
+            "" | "." => continue,
+            // Never serve anything outside of the output directory.
+            ".." => return None,
+            segment => path.push(segment),
+        }
+    }
+    if path.is_dir() {
+        path.push("index.html");
+    }
+    if path.is_file() {
+        return Some(path);
+    }
+    let with_extension = path.with_extension("html");
+    (path.extension().is_none() && with_extension.is_file()).then_some(with_extension)
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Adds the live reload script right before the closing body tag, or at the end of the page.
+fn inject_live_reload(html: &str) -> String {
+    match html.rfind("</body>") {
+        Some(index) => format!("{}{}{}", &html[..index], LIVE_RELOAD_SCRIPT, &html[index..]),
+        None => format!("{html}{LIVE_RELOAD_SCRIPT}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_resolve_path() -> io::Result<()> {
+        let tmp_dir = TempDir::with_prefix("serve")?;
+        let root = tmp_dir.path();
+        fs::create_dir_all(root.join("blog"))?;
+        fs::write(root.join("index.html"), "index")?;
+        fs::write(root.join("about.html"), "about")?;
+        fs::write(root.join("blog/index.html"), "blog")?;
+        fs::write(root.join("blog/my post.html"), "post")?;
+
+        assert_eq!(resolve_path(root, "/"), Some(root.join("index.html")));
+        assert_eq!(
+            resolve_path(root, "/about.html"),
+            Some(root.join("about.html"))
+        );
+        assert_eq!(resolve_path(root, "/about"), Some(root.join("about.html")));
+        assert_eq!(
+            resolve_path(root, "/blog/"),
+            Some(root.join("blog/index.html"))
+        );
+        assert_eq!(
+            resolve_path(root, "/blog/my%20post.html"),
+            Some(root.join("blog/my post.html"))
+        );
+        assert_eq!(resolve_path(root, "/missing.html"), None);
+        assert_eq!(resolve_path(root, "/../index.html"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_inject_live_reload() {
+        let html = inject_live_reload("<html><body><p>Hi</p></body></html>");
+        assert!(html.ends_with(&format!("{LIVE_RELOAD_SCRIPT}</body></html>")));
+
+        let html = inject_live_reload("<p>Hi</p>");
+        assert_eq!(html, format!("<p>Hi</p>{LIVE_RELOAD_SCRIPT}"));
+    }
+
+    #[test]
+    fn test_live_reload_wait_for_change() {
+        let live_reload = Arc::new(LiveReload::new());
+        assert_eq!(live_reload.wait_for_change(0, Duration::from_millis(10)), 0);
+
+        let notifier = live_reload.clone();
+        let handle = thread::spawn(move || notifier.notify());
+        assert_eq!(live_reload.wait_for_change(0, Duration::from_secs(5)), 1);
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_serve_html_with_live_reload_script() -> io::Result<()> {
+        let tmp_dir = TempDir::with_prefix("serve")?;
+        fs::write(
+            tmp_dir.path().join("index.html"),
+            "<html><body>Hello</body></html>",
+        )?;
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+        let root = tmp_dir.path().to_path_buf();
+        thread::spawn(move || serve(listener, root, Arc::new(LiveReload::new())));
+
+        let mut stream = TcpStream::connect(address)?;
+        stream.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("Content-Type: text/html; charset=utf-8"));
+        assert!(response.contains(&format!("Hello{LIVE_RELOAD_SCRIPT}</body>")));
+        Ok(())
+    }
+}
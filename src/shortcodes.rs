@@ -0,0 +1,202 @@
+use crate::jinja_processor::{JinjaEnvironment, PageContext};
+use crate::parser::find_template;
+use anyhow::Context;
+use pulldown_cmark::{Event, Parser, Tag};
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Folder of the shortcode templates, inside the template folder.
+pub(crate) const SHORTCODES_DIR: &str = "shortcodes";
+
+/// Matches `{{< name key="value" other=value >}}` and closing tags like `{{< /name >}}`.
+fn shortcode_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r#"\{\{<\s*(/?)([\w-]+)((?:\s+[\w-]+=(?:"[^"]*"|[^\s"]+?))*)\s*>\}\}"#).unwrap()
+    })
+}
+
+fn argument_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r#"([\w-]+)=(?:"([^"]*)"|([^\s"]+))"#).unwrap())
+}
+
+/// Where shortcodes are resolved from and how they are rendered.
+pub(crate) struct Shortcodes<'a> {
+    /// Folders of the shortcode templates, checked in order.
+    pub(crate) dirs: &'a [PathBuf],
+    /// If Some, shortcode templates are rendered with Jinja2, otherwise with the markers.
+    pub(crate) jinja: Option<&'a JinjaEnvironment>,
+    pub(crate) page: &'a PageContext,
+}
+
+/// Replaces the shortcodes of the markdown with their templates from `shortcodes/<name>.html`.
+/// Shortcodes with a closing tag get the text in between as `body`. Code spans and code
+/// blocks are left as they are.
+pub(crate) fn process_shortcodes(
+    markdown: &str,
+    shortcodes: &Shortcodes,
+) -> anyhow::Result<String> {
+    expand(markdown, &mask_code(markdown), shortcodes)
+}
+
+/// `markdown` with its code spans and code blocks blanked out, so the shortcodes in them are
+/// not matched. Offsets are the same as in `markdown`.
+fn mask_code(markdown: &str) -> String {
+    let mut masked = markdown.as_bytes().to_vec();
+    for (event, range) in Parser::new(markdown).into_offset_iter() {
+        if matches!(event, Event::Code(_) | Event::Start(Tag::CodeBlock(_))) {
+            masked[range].fill(b' ');
+        }
+    }
+    // Code starts and ends on char boundaries, so the text is still valid.
+    String::from_utf8(masked).unwrap()
+}
+
+/// Expands the shortcodes of `text`, found in `masked`, its copy without code.
+fn expand(text: &str, masked: &str, shortcodes: &Shortcodes) -> anyhow::Result<String> {
+    let mut result = String::new();
+    let mut offset = 0;
+    while let Some(captures) = shortcode_regex().captures(&masked[offset..]) {
+        let tag = captures.get(0).unwrap();
+        let name = &captures[2];
+        if !captures[1].is_empty() {
+            anyhow::bail!(
+                "Closing shortcode `{}` without an opening one",
+                tag.as_str()
+            );
+        }
+        result.push_str(&text[offset..offset + tag.start()]);
+        let after = offset + tag.end();
+
+        let closing = Regex::new(&format!(r"\{{\{{<\s*/{}\s*>\}}\}}", regex::escape(name)))?;
+        let body = match closing.find(&masked[after..]) {
+            Some(closing_tag) => {
+                let body = after..after + closing_tag.start();
+                offset = after + closing_tag.end();
+                Some(expand(&text[body.clone()], &masked[body], shortcodes)?)
+            }
+            None => {
+                offset = after;
+                None
+            }
+        };
+        let arguments = parse_arguments(&captures[3]);
+        result.push_str(
+            &render(name, &arguments, body.as_deref(), shortcodes)
+                .with_context(|| format!("Failed to render the shortcode `{}`", tag.as_str()))?,
+        );
+    }
+    result.push_str(&text[offset..]);
+    Ok(result)
+}
+
+fn parse_arguments(arguments: &str) -> HashMap<String, String> {
+    argument_regex()
+        .captures_iter(arguments)
+        .map(|captures: Captures| {
+            let value = captures.get(2).or_else(|| captures.get(3)).unwrap();
+            (captures[1].to_string(), value.as_str().to_string())
+        })
+        .collect()
+}
+
+fn render(
+    name: &str,
+    arguments: &HashMap<String, String>,
+    body: Option<&str>,
+    shortcodes: &Shortcodes,
+) -> anyhow::Result<String> {
+    let path = find_template(shortcodes.dirs, format!("{name}.html"));
+    let template = fs::read_to_string(&path)
+        .with_context(|| format!("Unknown shortcode '{name}', failed to read {path:?}"))?;
+    let body = body.unwrap_or_default().trim_matches('\n');
+    match shortcodes.jinja {
+        Some(jinja) => jinja.render_shortcode(
+            &format!("{SHORTCODES_DIR}/{name}.html"),
+            &template,
+            shortcodes.page,
+            arguments,
+            body,
+        ),
+        None => {
+            // Other markers, like $GENERETO['title'], are replaced with the rest of the page.
+            let mut output = template.replace("$GENERETO['body']", body);
+            for (key, value) in arguments {
+                output = output.replace(&format!("$GENERETO['{key}']"), value);
+            }
+            Ok(output.trim_end().to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page() -> PageContext {
+        PageContext::from_page_metadata(&Default::default())
+    }
+
+    #[test]
+    fn test_parse_arguments() {
+        let arguments = parse_arguments(r#" id="a b" width=300 caption="""#);
+        assert_eq!(arguments["id"], "a b");
+        assert_eq!(arguments["width"], "300");
+        assert_eq!(arguments["caption"], "");
+    }
+
+    #[test]
+    fn test_process_shortcodes_with_markers() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("note.html"),
+            "<div class=\"$GENERETO['kind']\">\n\n$GENERETO['body']\n\n</div>\n",
+        )?;
+        fs::write(dir.path().join("br.html"), "<br>")?;
+        let page = page();
+        let shortcodes = Shortcodes {
+            dirs: &[dir.path().to_path_buf()],
+            jinja: None,
+            page: &page,
+        };
+
+        let markdown = "Line{{< br >}}\n{{< note kind=\"warning\" >}}\n**Careful**{{< br >}}\n{{< /note >}}\n```\n{{< br >}}\n```\n";
+        assert_eq!(
+            process_shortcodes(markdown, &shortcodes)?,
+            "Line<br>\n<div class=\"warning\">\n\n**Careful**<br>\n\n</div>\n```\n{{< br >}}\n```\n"
+        );
+        assert!(process_shortcodes("{{< missing >}}", &shortcodes).is_err());
+        assert!(process_shortcodes("{{< /note >}}", &shortcodes).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_shortcodes_skips_code() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join("br.html"), "<br>")?;
+        let page = page();
+        let shortcodes = Shortcodes {
+            dirs: &[dir.path().to_path_buf()],
+            jinja: None,
+            page: &page,
+        };
+
+        for markdown in [
+            "Embed with `{{< youtube id=1 >}}`.\n",
+            "~~~\n{{< youtube id=1 >}}\n~~~\n",
+            "Example:\n\n    {{< youtube id=1 >}}\n",
+            "```\n{{< youtube id=1 >}}\n```\n",
+        ] {
+            assert_eq!(process_shortcodes(markdown, &shortcodes)?, markdown);
+        }
+        assert_eq!(
+            process_shortcodes("`{{< br >}}`{{< br >}}", &shortcodes)?,
+            "`{{< br >}}`<br>"
+        );
+        Ok(())
+    }
+}
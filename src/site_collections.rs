@@ -0,0 +1,182 @@
+use crate::jinja_processor::{JinjaEnvironment, PageContext, PageLinks};
+use crate::page_metadata::PageMetadata;
+use crate::parser::{get_anchor_id_from_title, MarkdownOptions};
+use crate::{blog, parser, taxonomy, DraftsOptions, GeneretoConfig, GeneretoConfigTaxonomy};
+use rayon::prelude::*;
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+
+/// Maximum number of related pages of a page.
+const MAX_RELATED_PAGES: usize = 5;
+
+/// Reads the metadata of every article and page before anything is rendered, so Jinja
+/// templates can list them through `site` and link them through `page.prev`, `page.next` and
+/// `page.related`. `pages` are the markdown pages outside of the blog, with their destination.
+pub(crate) fn load_site_collections(
+    genereto_config: &GeneretoConfig,
+    drafts_options: &DraftsOptions,
+    pages: &[(PathBuf, PathBuf)],
+    jinja: &mut JinjaEnvironment,
+) -> anyhow::Result<()> {
+    let markdown_options = MarkdownOptions::new(genereto_config);
+    let is_listed = |page: &PageMetadata| {
+        page.file_name != "error.html" && (!page.is_draft || drafts_options.is_dev())
+    };
+
+    let articles: Vec<(PathBuf, PageMetadata)> =
+        blog::load_articles_metadata(genereto_config, &markdown_options)?
+            .into_iter()
+            .filter(|(_, article)| is_listed(article))
+            .collect();
+    let mut pages = pages
+        .par_iter()
+        .map(|(entry_path, destination_path)| {
+            let mut metadata = parser::load_page_metadata(
+                entry_path,
+                "",
+                &genereto_config.url,
+                &markdown_options,
+            )?;
+            metadata.path = genereto_config.get_output_relative_path(destination_path);
+            Ok((entry_path.clone(), metadata))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    pages.retain(|(_, page)| is_listed(page));
+    pages.sort_by(|(_, a), (_, b)| a.path.cmp(&b.path));
+
+    let all_pages: Vec<&PageMetadata> = articles
+        .iter()
+        .chain(&pages)
+        .map(|(_, page)| page)
+        .collect();
+    jinja.site.taxonomies =
+        taxonomy::taxonomy_contexts(genereto_config, drafts_options, &all_pages);
+    jinja.site.articles = articles
+        .iter()
+        .map(|(_, article)| PageContext::from_page_metadata(article))
+        .collect();
+    jinja.site.pages = pages
+        .iter()
+        .map(|(_, page)| PageContext::from_page_metadata(page))
+        .collect();
+    jinja.set_links(page_links(&genereto_config.taxonomies, &articles, &pages));
+    Ok(())
+}
+
+/// Links of the articles and pages which have a page of their own, by source file.
+fn page_links(
+    taxonomies: &[GeneretoConfigTaxonomy],
+    articles: &[(PathBuf, PageMetadata)],
+    pages: &[(PathBuf, PageMetadata)],
+) -> HashMap<PathBuf, PageLinks> {
+    // Entries of blog.yml have no page, so they are not linked.
+    let articles: Vec<&(PathBuf, PageMetadata)> = articles
+        .iter()
+        .filter(|(_, article)| !article.path.is_empty())
+        .collect();
+    let all_pages: Vec<&(PathBuf, PageMetadata)> = articles.iter().copied().chain(pages).collect();
+    let terms: Vec<BTreeSet<String>> = all_pages
+        .iter()
+        .map(|(_, page)| related_terms(taxonomies, page))
+        .collect();
+
+    let mut links: HashMap<PathBuf, PageLinks> = HashMap::new();
+    for (idx, (source, _)) in all_pages.iter().enumerate() {
+        let mut related: Vec<(usize, &PageMetadata)> = all_pages
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != idx)
+            .map(|(other, (_, page))| (terms[idx].intersection(&terms[other]).count(), page))
+            .filter(|(shared, _)| *shared > 0)
+            .collect();
+        // The sort is stable: with as many terms in common, newer articles come first.
+        related.sort_by(|(a, _), (b, _)| b.cmp(a));
+        links.insert(
+            source.clone(),
+            PageLinks {
+                related: related
+                    .into_iter()
+                    .take(MAX_RELATED_PAGES)
+                    .map(|(_, page)| PageContext::from_page_metadata(page))
+                    .collect(),
+                ..Default::default()
+            },
+        );
+    }
+
+    // Articles are sorted newest first.
+    for (idx, (source, _)) in articles.iter().enumerate() {
+        let article_links = links.get_mut(source).unwrap();
+        article_links.prev = articles
+            .get(idx + 1)
+            .map(|(_, article)| PageContext::from_page_metadata(article));
+        article_links.next = idx
+            .checked_sub(1)
+            .map(|next| PageContext::from_page_metadata(&articles[next].1));
+    }
+    links
+}
+
+/// Taxonomy terms and keywords of `page`, prefixed by where they come from.
+fn related_terms(taxonomies: &[GeneretoConfigTaxonomy], page: &PageMetadata) -> BTreeSet<String> {
+    let taxonomy_terms = taxonomies.iter().flat_map(|taxonomy| {
+        page.taxonomy_terms(&taxonomy.name)
+            .into_iter()
+            .map(move |term| (taxonomy.name.as_str(), term))
+    });
+    let keywords = page
+        .keywords
+        .split(',')
+        .map(|keyword| ("keywords", keyword.to_string()));
+    taxonomy_terms
+        .chain(keywords)
+        .map(|(kind, term)| (kind, get_anchor_id_from_title(&term)))
+        .filter(|(_, slug)| !slug.is_empty())
+        .map(|(kind, slug)| format!("{kind}/{slug}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(path: &str, publish_date: &str, keywords: &str) -> (PathBuf, PageMetadata) {
+        let metadata = PageMetadata {
+            title: path.to_string(),
+            publish_date: publish_date.to_string(),
+            keywords: keywords.to_string(),
+            path: path.to_string(),
+            ..Default::default()
+        };
+        (PathBuf::from(path), metadata)
+    }
+
+    #[test]
+    fn test_page_links() {
+        let articles = [
+            page("blog/third.html", "2024-03-01", "rust, web"),
+            page("blog/second.html", "2024-02-01", "cooking"),
+            page("blog/first.html", "2024-01-01", "Rust"),
+        ];
+        let pages = [page("about.html", "", "web")];
+        let links = page_links(&[], &articles, &pages);
+
+        let second = &links[&PathBuf::from("blog/second.html")];
+        assert_eq!(second.prev.as_ref().unwrap().title, "blog/first.html");
+        assert_eq!(second.next.as_ref().unwrap().title, "blog/third.html");
+        assert!(second.related.is_empty());
+
+        let third = &links[&PathBuf::from("blog/third.html")];
+        assert!(third.next.is_none());
+        let related: Vec<&str> = third
+            .related
+            .iter()
+            .map(|page| page.title.as_str())
+            .collect();
+        assert_eq!(related, ["blog/first.html", "about.html"]);
+
+        let about = &links[&PathBuf::from("about.html")];
+        assert!(about.prev.is_none());
+        assert_eq!(about.related[0].title, "blog/third.html");
+    }
+}
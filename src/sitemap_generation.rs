@@ -0,0 +1,234 @@
+use crate::build_cache::BuildCache;
+use crate::fs_util::write_if_changed;
+use crate::page_metadata::PageMetadata;
+use crate::{DraftsOptions, GeneretoConfig};
+use anyhow::Context;
+use chrono::{DateTime, FixedOffset, NaiveDate, SecondsFormat};
+
+const SITEMAP_FILENAME: &str = "sitemap.xml";
+const ROBOTS_FILENAME: &str = "robots.txt";
+const DEFAULT_ROBOTS_RULES: &str = "User-agent: *\nAllow: /";
+
+/// An url listed in sitemap.xml
+#[derive(Debug, PartialEq)]
+struct SitemapUrl {
+    loc: String,
+    lastmod: Option<String>,
+    priority: Option<f32>,
+}
+
+/// Writes sitemap.xml with the compiled pages and blog articles in `pages`, and the index pages
+/// recorded in `build_cache`. Then writes robots.txt pointing to it.
+pub(crate) fn generate_sitemap(
+    genereto_config: &GeneretoConfig,
+    drafts_options: &DraftsOptions,
+    pages: &[&PageMetadata],
+    build_cache: &mut BuildCache,
+) -> anyhow::Result<()> {
+    if genereto_config.sitemap {
+        let mut urls: Vec<SitemapUrl> = pages
+            .iter()
+            .filter_map(|page| page_url(&genereto_config.url, page, drafts_options))
+            .collect();
+        // Index pages, like the blog and taxonomy listings, have no metadata of their own.
+        urls.extend(
+            build_cache
+                .outputs()
+                .filter(|output| output.extension().unwrap_or_default() == "html")
+                .map(|output| SitemapUrl {
+                    loc: format!(
+                        "{}/{}",
+                        genereto_config.url,
+                        genereto_config.get_output_relative_path(output)
+                    ),
+                    lastmod: None,
+                    priority: None,
+                }),
+        );
+        urls.sort_by(|a, b| a.loc.cmp(&b.loc));
+        urls.dedup_by(|a, b| a.loc == b.loc);
+
+        let sitemap_path = genereto_config.output_dir_path.join(SITEMAP_FILENAME);
+        write_if_changed(&sitemap_path, render_sitemap(&urls))
+            .with_context(|| format!("Failed to write {sitemap_path:?}"))?;
+        build_cache.record_output(&sitemap_path);
+    }
+
+    // A robots.txt in the content folder is copied as it is.
+    if genereto_config.content_path.join(ROBOTS_FILENAME).exists() {
+        return Ok(());
+    }
+    let robots_path = genereto_config.output_dir_path.join(ROBOTS_FILENAME);
+    write_if_changed(&robots_path, render_robots_txt(genereto_config))
+        .with_context(|| format!("Failed to write {robots_path:?}"))?;
+    build_cache.record_output(&robots_path);
+    Ok(())
+}
+
+/// Returns None for pages which are not published or opted out with `sitemap: false`.
+fn page_url(url: &str, page: &PageMetadata, drafts_options: &DraftsOptions) -> Option<SitemapUrl> {
+    if page.path.is_empty()
+        || page.file_name == "error.html"
+        || page.exclude_from_sitemap
+        || (page.is_draft && !drafts_options.is_dev())
+    {
+        return None;
+    }
+    let lastmod = [
+        (&page.last_modified_date, page.last_modified_at),
+        (&page.publish_date, page.published_at),
+    ]
+    .into_iter()
+    .find_map(|(date, parsed_date)| Some(w3c_date(date, parsed_date?)));
+    let priority = page.sitemap_priority.map(|priority| {
+        if !(0.0..=1.0).contains(&priority) {
+            warn!(
+                "Sitemap priority of {} should be between 0.0 and 1.0, got {priority}.",
+                page.path
+            );
+        }
+        priority.clamp(0.0, 1.0)
+    });
+    Some(SitemapUrl {
+        loc: format!("{url}/{}", page.path),
+        lastmod,
+        priority,
+    })
+}
+
+fn render_sitemap(urls: &[SitemapUrl]) -> String {
+    let mut sitemap = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    for url in urls {
+        sitemap.push_str("  <url>\n");
+        sitemap.push_str(&format!("    <loc>{}</loc>\n", escape_xml(&url.loc)));
+        if let Some(lastmod) = &url.lastmod {
+            sitemap.push_str(&format!("    <lastmod>{lastmod}</lastmod>\n"));
+        }
+        if let Some(priority) = url.priority {
+            sitemap.push_str(&format!("    <priority>{priority:.1}</priority>\n"));
+        }
+        sitemap.push_str("  </url>\n");
+    }
+    sitemap.push_str("</urlset>\n");
+    sitemap
+}
+
+fn render_robots_txt(genereto_config: &GeneretoConfig) -> String {
+    let rules = genereto_config
+        .robots_txt
+        .as_deref()
+        .unwrap_or(DEFAULT_ROBOTS_RULES)
+        .trim_end();
+    if genereto_config.sitemap {
+        format!(
+            "{rules}\n\nSitemap: {}/{SITEMAP_FILENAME}\n",
+            genereto_config.url
+        )
+    } else {
+        format!("{rules}\n")
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Days are kept as they are, times are written with their offset as the W3C Datetime
+/// format of sitemaps requires.
+fn w3c_date(date: &str, parsed_date: DateTime<FixedOffset>) -> String {
+    if NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok() {
+        date.to_string()
+    } else {
+        parsed_date.to_rfc3339_opts(SecondsFormat::Secs, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dates::parse_date;
+
+    #[test]
+    fn test_page_url() {
+        let page = PageMetadata {
+            path: "blog/first-post.html".to_string(),
+            file_name: "first-post.html".to_string(),
+            publish_date: "2024-01-01".to_string(),
+            published_at: parse_date("2024-01-01", None),
+            last_modified_date: "2024-02-03".to_string(),
+            last_modified_at: parse_date("2024-02-03", None),
+            sitemap_priority: Some(1.5),
+            ..Default::default()
+        };
+        assert_eq!(
+            page_url("https://example.com", &page, &DraftsOptions::Build),
+            Some(SitemapUrl {
+                loc: "https://example.com/blog/first-post.html".to_string(),
+                lastmod: Some("2024-02-03".to_string()),
+                priority: Some(1.0),
+            })
+        );
+
+        let draft = PageMetadata {
+            is_draft: true,
+            ..page.clone()
+        };
+        assert_eq!(
+            page_url("https://example.com", &draft, &DraftsOptions::Build),
+            None
+        );
+        assert!(page_url("https://example.com", &draft, &DraftsOptions::Dev).is_some());
+
+        let excluded = PageMetadata {
+            exclude_from_sitemap: true,
+            ..page.clone()
+        };
+        assert_eq!(
+            page_url("https://example.com", &excluded, &DraftsOptions::Build),
+            None
+        );
+
+        let scheduled = PageMetadata {
+            publish_date: "2024-01-01 10:30".to_string(),
+            published_at: parse_date("2024-01-01 10:30", Some(chrono_tz::Europe::Rome)),
+            last_modified_date: "2024-01-01 10:30".to_string(),
+            last_modified_at: None,
+            ..page.clone()
+        };
+        assert_eq!(
+            page_url("https://example.com", &scheduled, &DraftsOptions::Build)
+                .unwrap()
+                .lastmod
+                .as_deref(),
+            Some("2024-01-01T10:30:00+01:00")
+        );
+    }
+
+    #[test]
+    fn test_render_sitemap() {
+        let urls = [SitemapUrl {
+            loc: "https://example.com/a&b.html".to_string(),
+            lastmod: Some("2024-01-01".to_string()),
+            priority: Some(0.8),
+        }];
+        assert_eq!(
+            render_sitemap(&urls),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url>
+    <loc>https://example.com/a&amp;b.html</loc>
+    <lastmod>2024-01-01</lastmod>
+    <priority>0.8</priority>
+  </url>
+</urlset>
+"#
+        );
+    }
+}
@@ -0,0 +1,334 @@
+use crate::blog::{get_page_filename, pagination_context, render_index_markers};
+use crate::build_cache::BuildCache;
+use crate::config::{GeneretoConfig, GeneretoConfigTaxonomy};
+use crate::fs_util::write_if_changed;
+use crate::jinja_processor::{JinjaEnvironment, PageContext, TaxonomyContext, TermContext};
+use crate::page_metadata::PageMetadata;
+use crate::parser::get_anchor_id_from_title;
+use crate::rss_generation::{generate_rss, FeedInfo};
+use crate::DraftsOptions;
+use anyhow::Context;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+const INDEX_NAME: &str = "index.html";
+
+/// A term of a taxonomy with the pages using it, newest first.
+struct Term<'a> {
+    name: String,
+    slug: String,
+    pages: Vec<&'a PageMetadata>,
+}
+
+/// Generates the listing pages of every taxonomy declared in the config. Each taxonomy gets a
+/// folder in the output directory, with an index of its terms and one folder per term:
+/// `tags/index.html` and `tags/<term>/index.html`.
+pub(crate) fn generate_taxonomies(
+    genereto_config: &GeneretoConfig,
+    drafts_options: &DraftsOptions,
+    jinja: Option<&JinjaEnvironment>,
+    pages: &[&PageMetadata],
+    build_cache: &mut BuildCache,
+) -> anyhow::Result<()> {
+    if genereto_config.taxonomies.is_empty() {
+        return Ok(());
+    }
+
+    let pages = listed_pages(drafts_options, pages);
+    for taxonomy in &genereto_config.taxonomies {
+        generate_taxonomy(genereto_config, taxonomy, &pages, jinja, build_cache)
+            .with_context(|| format!("Failed to generate taxonomy '{}'", taxonomy.name))?;
+    }
+    Ok(())
+}
+
+/// Context of every taxonomy declared in the config, as in the taxonomy pages.
+pub(crate) fn taxonomy_contexts(
+    genereto_config: &GeneretoConfig,
+    drafts_options: &DraftsOptions,
+    pages: &[&PageMetadata],
+) -> BTreeMap<String, TaxonomyContext> {
+    let pages = listed_pages(drafts_options, pages);
+    genereto_config
+        .taxonomies
+        .iter()
+        .map(|taxonomy| {
+            let terms = collect_terms(&taxonomy.name, &pages);
+            let context = taxonomy_context(genereto_config, taxonomy, &terms);
+            (taxonomy.name.clone(), context)
+        })
+        .collect()
+}
+
+/// Pages which are listed in the taxonomies, newest first.
+fn listed_pages<'a>(
+    drafts_options: &DraftsOptions,
+    pages: &[&'a PageMetadata],
+) -> Vec<&'a PageMetadata> {
+    let mut pages: Vec<&PageMetadata> = pages
+        .iter()
+        .filter(|page| page.file_name != "error.html")
+        .filter(|page| !page.is_draft || drafts_options.is_dev())
+        .copied()
+        .collect();
+    // sort by published date
+    pages.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    pages
+}
+
+fn taxonomy_context(
+    genereto_config: &GeneretoConfig,
+    taxonomy: &GeneretoConfigTaxonomy,
+    terms: &[Term],
+) -> TaxonomyContext {
+    TaxonomyContext {
+        name: taxonomy.name.clone(),
+        terms: terms
+            .iter()
+            .map(|term| {
+                let path = format!("{}/{}/{INDEX_NAME}", taxonomy.name, term.slug);
+                let feed_path = genereto_config.get_feed_path(taxonomy, &term.slug);
+                TermContext {
+                    name: term.name.clone(),
+                    slug: term.slug.clone(),
+                    count: term.pages.len(),
+                    url: absolute_url(genereto_config, &path),
+                    path,
+                    feed_url: absolute_url(
+                        genereto_config,
+                        &genereto_config.get_output_relative_path(&feed_path),
+                    ),
+                }
+            })
+            .collect(),
+    }
+}
+
+fn generate_taxonomy(
+    genereto_config: &GeneretoConfig,
+    taxonomy: &GeneretoConfigTaxonomy,
+    pages: &[&PageMetadata],
+    jinja: Option<&JinjaEnvironment>,
+    build_cache: &mut BuildCache,
+) -> anyhow::Result<()> {
+    let destination = genereto_config.output_dir_path.join(&taxonomy.name);
+    let template = load_template(&taxonomy.template, genereto_config)?;
+    let index_template = load_template(&taxonomy.index_template, genereto_config)?;
+
+    let terms = collect_terms(&taxonomy.name, pages);
+    let taxonomy_context = taxonomy_context(genereto_config, taxonomy, &terms);
+
+    for (term, term_context) in terms.iter().zip(&taxonomy_context.terms) {
+        let term_destination = destination.join(&term.slug);
+        fs::create_dir_all(&term_destination)?;
+        // Term pages are two folders below the output folder.
+        let articles: Vec<PageMetadata> = term
+            .pages
+            .iter()
+            .map(|page| linked_from(page, "../../"))
+            .collect();
+        let articles: Vec<&PageMetadata> = articles.iter().collect();
+
+        let chunks: Vec<&[&PageMetadata]> = match genereto_config.blog.max_entries_per_page {
+            Some(max_per_page) => articles.chunks(max_per_page).collect(),
+            None => vec![&articles],
+        };
+        let total_pages = chunks.len();
+        for (page_idx, chunk) in chunks.into_iter().enumerate() {
+            let page_number = page_idx + 1;
+            let pagination = genereto_config
+                .blog
+                .max_entries_per_page
+                .map(|_| pagination_context(Path::new(INDEX_NAME), page_number, total_pages));
+
+            let content = if let Some(jinja) = jinja {
+                let page_contexts: Vec<PageContext> = chunk
+                    .iter()
+                    .map(|md| PageContext::from_page_metadata(md))
+                    .collect();
+                jinja.render_taxonomy_page(
+                    &genereto_config.get_template_name(&taxonomy.template),
+                    &template,
+                    &taxonomy_context,
+                    Some(term_context),
+                    &page_contexts,
+                    pagination.as_ref(),
+                )?
+            } else {
+                render_index_markers(
+                    template.clone(),
+                    &taxonomy.template,
+                    chunk,
+                    &term.name,
+                    genereto_config,
+                    pagination.as_ref(),
+                )?
+                .replace("$GENERETO['feed_url']", &term_context.feed_url)
+            };
+            let destination_path =
+                term_destination.join(get_page_filename(Path::new(INDEX_NAME), page_number));
+            write_if_changed(&destination_path, content)
+                .with_context(|| format!("Failed to write {destination_path:?}"))?;
+            build_cache.record_output(&destination_path);
+        }
+
+        let feed_path = genereto_config.get_feed_path(taxonomy, &term.slug);
+        if let Some(parent) = feed_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let feed = FeedInfo {
+            title: format!("{} - {}", genereto_config.title, term.name),
+            link_by_file_name: false,
+            ..FeedInfo::new(genereto_config)
+        };
+        generate_rss(
+            &feed,
+            term.pages.iter().map(|page| (*page).clone()).collect(),
+            &feed_path,
+        )
+        .with_context(|| format!("Failed to write the feed {feed_path:?}"))?;
+        build_cache.record_output(&feed_path);
+    }
+
+    fs::create_dir_all(&destination)?;
+    let content = if let Some(jinja) = jinja {
+        jinja.render_taxonomy_page(
+            &genereto_config.get_template_name(&taxonomy.index_template),
+            &index_template,
+            &taxonomy_context,
+            None,
+            &[],
+            None,
+        )?
+    } else {
+        // Terms are listed with the same markers used for articles: the title is the term
+        // name and the file name links to the term page.
+        let entries: Vec<PageMetadata> = terms
+            .iter()
+            .map(|term| PageMetadata {
+                title: term.name.clone(),
+                file_name: format!("{}/{INDEX_NAME}", term.slug),
+                page_name: term.slug.clone(),
+                website_url: genereto_config.url.clone(),
+                custom_metadata: [("term_count".to_string(), term.pages.len().into())].into(),
+                ..Default::default()
+            })
+            .collect();
+        let entries: Vec<&PageMetadata> = entries.iter().collect();
+        render_index_markers(
+            index_template,
+            &taxonomy.index_template,
+            &entries,
+            &taxonomy.name,
+            genereto_config,
+            None,
+        )?
+    };
+    let destination_path = destination.join(INDEX_NAME);
+    write_if_changed(&destination_path, content)
+        .with_context(|| format!("Failed to write {destination_path:?}"))?;
+    build_cache.record_output(&destination_path);
+    Ok(())
+}
+
+fn absolute_url(genereto_config: &GeneretoConfig, path: &str) -> String {
+    format!("{}/{path}", genereto_config.url.trim_end_matches('/'))
+}
+
+fn load_template(path: &Path, genereto_config: &GeneretoConfig) -> anyhow::Result<String> {
+    let template = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read the taxonomy template at path: {path:?}"))?;
+    crate::parser::process_includes_in(&template, &genereto_config.template_dirs)
+}
+
+/// Groups `pages` by the terms they use. Terms differing only in case or punctuation are
+/// merged, keeping the name of the first occurrence. Terms are sorted by slug.
+fn collect_terms<'a>(taxonomy_name: &str, pages: &[&'a PageMetadata]) -> Vec<Term<'a>> {
+    let mut terms: BTreeMap<String, Term> = BTreeMap::new();
+    for page in pages {
+        let mut page_slugs = BTreeSet::new();
+        for name in page.taxonomy_terms(taxonomy_name) {
+            let slug = get_anchor_id_from_title(&name);
+            if slug.is_empty() {
+                warn!(
+                    "Skipping {taxonomy_name} term '{name}' of {}: it has no letters or digits.",
+                    page.file_name
+                );
+                continue;
+            }
+            if !page_slugs.insert(slug.clone()) {
+                continue;
+            }
+            terms
+                .entry(slug.clone())
+                .or_insert_with(|| Term {
+                    name,
+                    slug,
+                    pages: vec![],
+                })
+                .pages
+                .push(page);
+        }
+    }
+    terms.into_values().collect()
+}
+
+/// Returns a copy of `page` whose file name links to it from a page `root` below the output
+/// folder. Entries without a page of their own are left untouched.
+fn linked_from(page: &PageMetadata, root: &str) -> PageMetadata {
+    let mut page = page.clone();
+    if !page.path.is_empty() {
+        page.file_name = format!("{root}{}", page.path);
+    }
+    page
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(path: &str, publish_date: &str, tags: &str) -> PageMetadata {
+        PageMetadata {
+            title: path.to_string(),
+            publish_date: publish_date.to_string(),
+            file_name: path.rsplit('/').next().unwrap().to_string(),
+            path: path.to_string(),
+            custom_metadata: [("tags".to_string(), tags.into())].into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_collect_terms() {
+        let first = page("blog/first.html", "2024-01-02", "Rust, web, rust");
+        let second = page("about.html", "2024-01-01", "C++, Web Development");
+        let untagged = page("index.html", "2024-01-01", "");
+        let pages = [&first, &second, &untagged];
+
+        let terms = collect_terms("tags", &pages);
+        let slugs: Vec<&str> = terms.iter().map(|term| term.slug.as_str()).collect();
+        assert_eq!(slugs, ["c", "rust", "web", "web-development"]);
+
+        let rust = &terms[1];
+        assert_eq!(rust.name, "Rust");
+        assert_eq!(rust.pages.len(), 1);
+        assert_eq!(terms[3].name, "Web Development");
+        assert!(collect_terms("categories", &pages).is_empty());
+    }
+
+    #[test]
+    fn test_linked_from() {
+        let post = page("blog/first.html", "2024-01-01", "");
+        assert_eq!(
+            linked_from(&post, "../../").file_name,
+            "../../blog/first.html"
+        );
+
+        let external = PageMetadata {
+            file_name: "blog.html".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(linked_from(&external, "../../").file_name, "blog.html");
+    }
+}
@@ -8,11 +8,11 @@ use crate::GeneretoConfig;
 /// Scan markdown files for image/asset references and verify they exist on disk.
 pub fn check(config: &GeneretoConfig) -> Vec<VerifyIssue> {
     let mut issues = Vec::new();
-    collect_from_dir(&config.content_path, &config.content_path, &mut issues);
+    collect_from_dir(&config.content_path, &mut issues);
     issues
 }
 
-fn collect_from_dir(dir: &Path, content_root: &Path, issues: &mut Vec<VerifyIssue>) {
+fn collect_from_dir(dir: &Path, issues: &mut Vec<VerifyIssue>) {
     let entries = match std::fs::read_dir(dir) {
         Ok(e) => e,
         Err(_) => return,
@@ -24,7 +24,7 @@ fn collect_from_dir(dir: &Path, content_root: &Path, issues: &mut Vec<VerifyIssu
         };
         let path = entry.path();
         if path.is_dir() {
-            collect_from_dir(&path, content_root, issues);
+            collect_from_dir(&path, issues);
         } else if path.extension().and_then(|e| e.to_str()) == Some("md") {
             check_file(&path, issues);
         }
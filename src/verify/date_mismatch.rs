@@ -2,6 +2,7 @@ use std::path::Path;
 
 use regex::Regex;
 
+use crate::dates::parse_date;
 use crate::parser::compile_page_phase_1;
 use crate::verify::{Check, Severity, VerifyIssue};
 use crate::GeneretoConfig;
@@ -66,7 +67,12 @@ fn check_file(filepath: &Path, issues: &mut Vec<VerifyIssue>) {
         return;
     }
 
-    if publish_date != &file_date {
+    // Only the day is compared, the publish date can also have the time.
+    let publish_day = parse_date(publish_date, None).map_or_else(
+        || publish_date.clone(),
+        |date| date.format("%Y-%m-%d").to_string(),
+    );
+    if publish_day != file_date {
         issues.push(VerifyIssue {
             check: Check::DateMismatch,
             severity: Severity::Warning,
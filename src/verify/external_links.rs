@@ -48,7 +48,7 @@ fn save_cache(project_path: &Path, cache: &HashMap<String, CacheEntry>) {
     let mut lines = vec!["url,last_checked,status,ignore".to_string()];
 
     let mut entries: Vec<_> = cache.iter().collect();
-    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    entries.sort_by_key(|(url, _)| *url);
 
     for (url, entry) in entries {
         lines.push(format!(
@@ -87,8 +87,7 @@ fn check_url_reachable(url: &str) -> (String, bool) {
         }
         Err(ureq::Error::StatusCode(code)) => {
             let status = code.to_string();
-            let code_num: u16 = code.into();
-            let ok = code_num < 400;
+            let ok = code < 400;
             (status, ok)
         }
         Err(_) => ("unreachable".to_string(), false),
@@ -103,6 +102,15 @@ fn collect_external_urls(output_dir: &Path) -> Vec<String> {
 }
 
 fn collect_urls_from_dir(dir: &Path, urls: &mut std::collections::BTreeSet<String>) {
+    let url_re = Regex::new(r#"(?:href|src)="(https?://[^"]+)""#).unwrap();
+    collect_urls_from_dir_with(dir, &url_re, urls);
+}
+
+fn collect_urls_from_dir_with(
+    dir: &Path,
+    url_re: &Regex,
+    urls: &mut std::collections::BTreeSet<String>,
+) {
     let entries = match std::fs::read_dir(dir) {
         Ok(e) => e,
         Err(_) => return,
@@ -114,10 +122,9 @@ fn collect_urls_from_dir(dir: &Path, urls: &mut std::collections::BTreeSet<Strin
         };
         let path = entry.path();
         if path.is_dir() {
-            collect_urls_from_dir(&path, urls);
+            collect_urls_from_dir_with(&path, url_re, urls);
         } else if path.extension().and_then(|e| e.to_str()) == Some("html") {
             if let Ok(content) = std::fs::read_to_string(&path) {
-                let url_re = Regex::new(r#"(?:href|src)="(https?://[^"]+)""#).unwrap();
                 for cap in url_re.captures_iter(&content) {
                     urls.insert(cap[1].to_string());
                 }
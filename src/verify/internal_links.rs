@@ -67,9 +67,9 @@ fn check_html_file(filepath: &Path, output_root: &Path, issues: &mut Vec<VerifyI
                 continue;
             }
 
-            let resolved = if path_part.starts_with('/') {
+            let resolved = if let Some(absolute) = path_part.strip_prefix('/') {
                 // Absolute path relative to output root
-                output_root.join(&path_part[1..])
+                output_root.join(absolute)
             } else {
                 // Relative path from file's directory
                 file_dir.join(path_part)
@@ -0,0 +1,145 @@
+use crate::config::CONFIG_FILENAME;
+use crate::{build, DraftsOptions, GeneretoConfig};
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// A rebuild starts only once no file changed for this long, so that editors saving several
+/// files at once (or writing a file in multiple steps) trigger a single build.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Watches the content folder, the template folder and config.yml, rebuilding the project
+/// after every change. `load_config` is called before each rebuild, so config changes are
+/// picked up too, and `on_build` runs after every successful build.
+///
+/// Config and build errors are logged, and the watcher keeps going: fixing the file is
+/// enough to get a new build.
+pub fn watch<L, B>(
+    mut config: GeneretoConfig,
+    load_config: L,
+    drafts_options: &DraftsOptions,
+    mut on_build: B,
+) -> !
+where
+    L: Fn() -> anyhow::Result<GeneretoConfig>,
+    B: FnMut(&GeneretoConfig),
+{
+    info!(
+        "Watching {}, {} and {} for changes.",
+        config.content_path.display(),
+        config
+            .template_dirs
+            .iter()
+            .map(|template_dir| template_dir.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+        CONFIG_FILENAME
+    );
+    let mut last_modified = latest_modification(&config);
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        if latest_modification(&config) == last_modified {
+            continue;
+        }
+        last_modified = wait_until_settled(&config);
+        info!("Change detected, rebuilding.");
+
+        match load_config() {
+            Ok(new_config) => config = new_config,
+            Err(e) => {
+                error!("Failed to reload config, fix it to resume building: {e:?}");
+                continue;
+            }
+        }
+        match build(&config, drafts_options) {
+            Ok(_) => {
+                info!("Rebuild completed.");
+                on_build(&config);
+            }
+            Err(e) => error!("Build failed: {e:?}"),
+        }
+    }
+}
+
+/// Waits for a burst of changes to be over. Returns the most recent modification time.
+fn wait_until_settled(config: &GeneretoConfig) -> Option<SystemTime> {
+    let mut modified = latest_modification(config);
+    loop {
+        thread::sleep(DEBOUNCE);
+        let current = latest_modification(config);
+        if current == modified {
+            return current;
+        }
+        modified = current;
+    }
+}
+
+/// Most recent modification time across the content folder, the template folders and
+/// config.yml. Directories are included, so deleting or renaming a file is noticed too.
+fn latest_modification(config: &GeneretoConfig) -> Option<SystemTime> {
+    [
+        config.content_path.clone(),
+        config.project_path.join(CONFIG_FILENAME),
+    ]
+    .into_iter()
+    .chain(config.template_dirs.iter().cloned())
+    .filter_map(|path| latest_modification_in(&path))
+    .max()
+}
+
+fn latest_modification_in(path: &Path) -> Option<SystemTime> {
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+    if !path.is_dir() {
+        return modified;
+    }
+    fs::read_dir(path)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| latest_modification_in(&entry.path()))
+        .chain(modified)
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io;
+    use tempfile::TempDir;
+
+    fn set_modified(path: &Path, modified: SystemTime) -> io::Result<()> {
+        File::options()
+            .write(true)
+            .open(path)?
+            .set_modified(modified)
+    }
+
+    #[test]
+    fn test_latest_modification() -> anyhow::Result<()> {
+        let tmp_dir = TempDir::with_prefix("watch")?;
+        let project_path = tmp_dir.path();
+        fs::create_dir_all(project_path.join("content/blog"))?;
+        fs::create_dir_all(project_path.join("templates/main"))?;
+        fs::write(project_path.join("config.yml"), "template: main\n")?;
+        fs::write(project_path.join("content/blog/post.md"), "content")?;
+        fs::write(project_path.join("templates/main/index.html"), "template")?;
+        let config = GeneretoConfig::load_from_folder(project_path)?;
+
+        let before = latest_modification(&config).unwrap();
+        let later = before + Duration::from_secs(60);
+
+        set_modified(&project_path.join("content/blog/post.md"), later)?;
+        assert_eq!(latest_modification(&config), Some(later));
+
+        let even_later = later + Duration::from_secs(60);
+        set_modified(&project_path.join("templates/main/index.html"), even_later)?;
+        assert_eq!(latest_modification(&config), Some(even_later));
+
+        let latest = even_later + Duration::from_secs(60);
+        set_modified(&project_path.join("config.yml"), latest)?;
+        assert_eq!(latest_modification(&config), Some(latest));
+        Ok(())
+    }
+}
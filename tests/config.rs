@@ -1,4 +1,4 @@
-use genereto::{GeneretoConfig, GeneretoConfigBlog};
+use genereto::{FeedFormat, GeneretoConfig, GeneretoConfigBlog};
 use std::io;
 use std::path::PathBuf;
 use tempfile::TempDir;
@@ -53,6 +53,19 @@ fn test_load_config_without_blog() {
             title: Some("Custom Blog Title".into()),
             max_entries_per_page: None,
         },
+        taxonomies: vec![],
+        feeds: vec![FeedFormat::Rss],
+        language: "en-us".into(),
+        author: None,
+        feed_full_content: false,
+        sitemap: true,
+        robots_txt: None,
+        search_index: false,
+        highlight: None,
+        markdown: Default::default(),
+        timezone: None,
+        expired_template: None,
+        template_dirs: vec![PathBuf::from("/custom/template/path/test_template")],
     };
 
     let expected_no_blog = GeneretoConfig {
@@ -75,6 +88,19 @@ fn test_load_config_without_blog() {
             default_cover_image: Some("".into()),
             max_entries_per_page: None,
         },
+        taxonomies: vec![],
+        feeds: vec![FeedFormat::Rss],
+        language: "en-us".into(),
+        author: None,
+        feed_full_content: false,
+        sitemap: true,
+        robots_txt: None,
+        search_index: false,
+        highlight: None,
+        markdown: Default::default(),
+        timezone: None,
+        expired_template: None,
+        template_dirs: vec!["template".into()],
     };
 
     let no_blog = r#"
@@ -168,3 +194,79 @@ fn store_config(cfg: &str) -> io::Result<TempDir> {
     std::fs::write(config_path, cfg)?;
     Ok(temp)
 }
+
+#[test]
+fn test_load_config_output_dirname() {
+    let temp = store_config("template: main\n").unwrap();
+    let received = GeneretoConfig::load_from_folder(temp.path()).unwrap();
+    assert_eq!(received.output_dir_path, temp.path().join("output"));
+
+    let temp = store_config("template: main\noutput_dirname: public\nblog:\n  destination: blog\n")
+        .unwrap();
+    let received = GeneretoConfig::load_from_folder(temp.path()).unwrap();
+    assert_eq!(received.output_dir_path, temp.path().join("public"));
+    assert_eq!(received.blog.destination, temp.path().join("public/blog"));
+
+    let temp = store_config(
+        "template: main\noutput_dirname: /var/www/public\nblog:\n  destination: blog\n",
+    )
+    .unwrap();
+    let received = GeneretoConfig::load_from_folder(temp.path()).unwrap();
+    assert_eq!(received.output_dir_path, PathBuf::from("/var/www/public"));
+    assert_eq!(
+        received.blog.destination,
+        PathBuf::from("/var/www/public/blog")
+    );
+}
+
+#[test]
+fn test_config_with_output_dir() {
+    let temp = store_config("template: main\nblog:\n  destination: blog\n").unwrap();
+    let received = GeneretoConfig::load_from_folder(temp.path())
+        .unwrap()
+        .with_output_dir("/tmp/site")
+        .unwrap();
+    assert_eq!(received.output_dir_path, PathBuf::from("/tmp/site"));
+    assert_eq!(received.blog.destination, PathBuf::from("/tmp/site/blog"));
+}
+
+#[test]
+fn test_config_rejects_output_dir_overlapping_the_project() {
+    for output_dirname in ["\"\"", ".", "..", "content", "templates", "templates/main"] {
+        let temp = store_config(&format!(
+            "template: main\noutput_dirname: {output_dirname}\n"
+        ))
+        .unwrap();
+        let error = GeneretoConfig::load_from_folder(temp.path()).unwrap_err();
+        assert!(
+            error.to_string().contains("contains the"),
+            "{output_dirname}: {error}"
+        );
+    }
+
+    for output_dirname in ["content/public", "templates/main/output"] {
+        let temp = store_config(&format!(
+            "template: main\noutput_dirname: {output_dirname}\n"
+        ))
+        .unwrap();
+        let error = GeneretoConfig::load_from_folder(temp.path()).unwrap_err();
+        assert!(
+            error.to_string().contains("is inside the"),
+            "{output_dirname}: {error}"
+        );
+    }
+
+    let temp = store_config("template: main\n").unwrap();
+    for output_dir in [
+        temp.path().to_path_buf(),
+        temp.path().join("content"),
+        temp.path().join("content/../templates"),
+        temp.path().join("content/public"),
+    ] {
+        let config = GeneretoConfig::load_from_folder(temp.path()).unwrap();
+        assert!(
+            config.with_output_dir(&output_dir).is_err(),
+            "{output_dir:?}"
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use genereto::DraftsOptions;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn create_project(project_path: &Path, expired_template: &str) -> anyhow::Result<()> {
+    fs::create_dir_all(project_path.join("templates/default"))?;
+    fs::create_dir_all(project_path.join("content/blog"))?;
+    fs::write(
+        project_path.join("config.yml"),
+        format!(
+            r#"
+template: default
+title: Test Blog
+url: http://test.com
+blog:
+  base_template: blog-index.html
+  destination: blog
+  generate_single_pages: true
+{expired_template}
+"#
+        ),
+    )?;
+    let template =
+        "<html><body>$GENERETO['title']<!-- start_content -->\n<!-- end_content --></body></html>";
+    fs::write(project_path.join("templates/default/index.html"), template)?;
+    fs::write(project_path.join("templates/default/blog.html"), template)?;
+    fs::write(
+        project_path.join("templates/default/blog-index.html"),
+        "<ul><!-- start_content --><li>$GENERETO['title']</li><!-- end_content --></ul>",
+    )?;
+    fs::write(
+        project_path.join("templates/default/expired.html"),
+        "<p>$GENERETO['title'] ended on $GENERETO['expiry_date'].</p><!-- start_content -->\n<!-- end_content -->",
+    )?;
+    let next_year = chrono::Utc::now() + chrono::Duration::days(365);
+    for (name, expiry_date) in [
+        ("past-event", "2024-01-20".to_string()),
+        ("next-event", next_year.format("%Y-%m-%d").to_string()),
+    ] {
+        fs::write(
+            project_path.join(format!("content/blog/{name}.md")),
+            format!(
+                "---\ntitle: {name}\npublish_date: '2024-01-01'\nexpiry_date: '{expiry_date}'\n---\n\nCome along!\n"
+            ),
+        )?;
+    }
+    fs::write(
+        project_path.join("content/offer.md"),
+        "---\ntitle: Offer\nexpiry_date: '2024-02-01T12:00:00+01:00'\n---\n\nHalf price.\n",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_expired_pages_are_hidden() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("expiry_date")?;
+    let project_path = tmp_dir.path();
+    create_project(project_path, "")?;
+
+    genereto::run(project_path.into(), DraftsOptions::Hide)?;
+    let output = project_path.join("output");
+
+    assert!(output.join("blog/next-event.html").exists());
+    assert!(!output.join("blog/past-event.html").exists());
+    assert!(!output.join("offer.html").exists());
+    let index = fs::read_to_string(output.join("blog/index.html"))?;
+    assert!(index.contains("next-event") && !index.contains("past-event"));
+    let rss = fs::read_to_string(output.join("rss.xml"))?;
+    assert!(rss.contains("next-event") && !rss.contains("past-event"));
+    let sitemap = fs::read_to_string(output.join("sitemap.xml"))?;
+    assert!(sitemap.contains("next-event") && !sitemap.contains("past-event"));
+    assert!(!sitemap.contains("offer"));
+    Ok(())
+}
+
+#[test]
+fn test_expired_template() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("expiry_date")?;
+    let project_path = tmp_dir.path();
+    create_project(project_path, "expired_template: expired.html")?;
+
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+    let output = project_path.join("output");
+
+    let past_event = fs::read_to_string(output.join("blog/past-event.html"))?;
+    assert!(past_event.starts_with("<p>past-event ended on 2024-01-20.</p>"));
+    let offer = fs::read_to_string(output.join("offer.html"))?;
+    assert!(offer.starts_with("<p>Offer ended on 2024-02-01T12:00:00+01:00.</p>"));
+    let next_event = fs::read_to_string(output.join("blog/next-event.html"))?;
+    assert!(next_event.starts_with("<html><body>next-event"));
+    // Still built for the old links, but not listed anywhere.
+    let index = fs::read_to_string(output.join("blog/index.html"))?;
+    assert!(!index.contains("past-event"));
+    let sitemap = fs::read_to_string(output.join("sitemap.xml"))?;
+    assert!(!sitemap.contains("past-event") && !sitemap.contains("offer"));
+    Ok(())
+}
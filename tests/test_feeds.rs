@@ -0,0 +1,93 @@
+use genereto::DraftsOptions;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_generate_configured_feeds() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("feeds")?;
+    let project_path = tmp_dir.path();
+    fs::create_dir_all(project_path.join("templates/default"))?;
+    fs::create_dir_all(project_path.join("content/blog"))?;
+    fs::write(
+        project_path.join("config.yml"),
+        r#"
+template: default
+title: Test Blog
+url: http://test.com
+description: Test blog description
+timezone: UTC
+blog:
+  destination: blog
+feeds: [atom, json]
+"#,
+    )?;
+    let template = "<html><body><!-- start_content -->\n<!-- end_content --></body></html>";
+    fs::write(project_path.join("templates/default/index.html"), template)?;
+    fs::write(project_path.join("templates/default/blog.html"), template)?;
+    fs::write(
+        project_path.join("content/blog/first-post.md"),
+        "---\ntitle: First Post\npublish_date: '2024-01-01'\n---\n\nSome content.\n",
+    )?;
+
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+    let output = project_path.join("output");
+
+    assert!(!output.join("rss.xml").exists());
+    let atom = fs::read_to_string(output.join("atom.xml"))?;
+    assert!(atom.contains(r#"<feed xmlns="http://www.w3.org/2005/Atom" xml:lang="en-us">"#));
+    assert!(atom.contains("<id>http://test.com/blog/first-post.html</id>"));
+    assert!(atom.contains("<updated>2024-01-01T00:00:00+00:00</updated>"));
+    let json_feed = fs::read_to_string(output.join("feed.json"))?;
+    assert!(json_feed.contains(r#""version": "https://jsonfeed.org/version/1.1""#));
+    assert!(json_feed.contains(r#""url": "http://test.com/blog/first-post.html""#));
+    Ok(())
+}
+
+#[test]
+fn test_rss_items_with_full_content() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("feeds")?;
+    let project_path = tmp_dir.path();
+    fs::create_dir_all(project_path.join("templates/default"))?;
+    fs::create_dir_all(project_path.join("content/blog/first-post"))?;
+    fs::write(
+        project_path.join("config.yml"),
+        r#"
+template: default
+title: Test Blog
+url: http://test.com
+description: Test blog description
+language: it
+author: blog@test.com (Test Author)
+feed_full_content: true
+blog:
+  destination: blog
+"#,
+    )?;
+    let template = "<html><body><!-- start_content -->\n<!-- end_content --></body></html>";
+    fs::write(project_path.join("templates/default/index.html"), template)?;
+    fs::write(project_path.join("templates/default/blog.html"), template)?;
+    fs::write(
+        project_path.join("content/blog/first-post.md"),
+        "---\ntitle: First Post\npublish_date: '2024-01-01'\nkeywords: rust, web\ncover_image: cover.jpg\n---\n\nSome **bold** content.\n",
+    )?;
+    fs::write(
+        project_path.join("content/blog/first-post/cover.jpg"),
+        "1234",
+    )?;
+
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+
+    let rss = fs::read_to_string(project_path.join("output/rss.xml"))?;
+    assert!(rss.contains("<language>it</language>"));
+    // The blog feed links articles by file name, as it always did.
+    assert!(rss.contains("<guid>http://test.com/first-post.html</guid>"));
+    assert!(rss.contains("<category>rust</category>"));
+    assert!(rss.contains("<category>web</category>"));
+    assert!(rss.contains("<author>blog@test.com (Test Author)</author>"));
+    assert!(rss.contains(
+        r#"<enclosure url="http://test.com/blog/first-post/cover.jpg" length="4" type="image/jpeg"/>"#
+    ));
+    assert!(rss.contains("<content:encoded><![CDATA["));
+    assert!(rss.contains("<strong>bold</strong>"));
+    Ok(())
+}
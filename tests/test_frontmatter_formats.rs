@@ -0,0 +1,50 @@
+use genereto::DraftsOptions;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_toml_and_json_frontmatter() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("frontmatter_formats")?;
+    let project_path = tmp_dir.path();
+    fs::create_dir_all(project_path.join("templates/default"))?;
+    fs::create_dir_all(project_path.join("content/blog"))?;
+    fs::write(
+        project_path.join("config.yml"),
+        "template: default\ntitle: Test\nurl: http://test.com\nblog:\n  base_template: blog-index.html\n  destination: blog\n",
+    )?;
+    let template = "<h1>$GENERETO['title']</h1><p>$GENERETO['publish_date'] $GENERETO['tags']</p><!-- start_content -->\n<!-- end_content -->";
+    fs::write(project_path.join("templates/default/index.html"), template)?;
+    fs::write(project_path.join("templates/default/blog.html"), template)?;
+    fs::write(
+        project_path.join("templates/default/blog-index.html"),
+        "<!-- start_content --><a>$GENERETO['title']</a><!-- end_content -->",
+    )?;
+    fs::write(
+        project_path.join("content/blog/from-hugo.md"),
+        "+++\ntitle = \"From Hugo\"\npublish_date = 2024-01-15\ntags = [\"rust\", \"web\"]\n+++\n\nMigrated.\n",
+    )?;
+    fs::write(
+        project_path.join("content/about.md"),
+        "{\n  \"title\": \"About\",\n  \"publish_date\": \"2024-01-01\",\n  \"tags\": \"json\"\n}\n\nAbout me.\n",
+    )?;
+
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+    let output = project_path.join("output");
+
+    let post = fs::read_to_string(output.join("blog/from-hugo.html"))?;
+    assert!(post.contains("<h1>From Hugo</h1><p>2024-01-15 rust, web</p>"));
+    assert!(post.contains("<p>Migrated.</p>"));
+    let index = fs::read_to_string(output.join("blog/index.html"))?;
+    assert!(index.contains("<a>From Hugo</a>"));
+    let about = fs::read_to_string(output.join("about.html"))?;
+    assert!(about.contains("<h1>About</h1><p>2024-01-01 json</p>"));
+    assert!(about.contains("<p>About me.</p>"));
+
+    fs::write(
+        project_path.join("content/about.md"),
+        "+++\ntitle = \"About\"\ndraft = \n+++\n\nAbout me.\n",
+    )?;
+    let error = genereto::run(project_path.into(), DraftsOptions::Build).unwrap_err();
+    assert!(format!("{error:#}").contains("line 2"));
+    Ok(())
+}
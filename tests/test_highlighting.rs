@@ -0,0 +1,74 @@
+use genereto::DraftsOptions;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn create_project(project_path: &Path, highlight_config: &str) -> anyhow::Result<()> {
+    fs::create_dir_all(project_path.join("templates/default"))?;
+    fs::create_dir_all(project_path.join("content"))?;
+    fs::write(
+        project_path.join("config.yml"),
+        format!("template: default\ntitle: Test\nurl: http://test.com\n{highlight_config}"),
+    )?;
+    fs::write(
+        project_path.join("templates/default/index.html"),
+        "<html><body><!-- start_content -->\n<!-- end_content --></body></html>",
+    )?;
+    fs::write(
+        project_path.join("content/index.md"),
+        "---\ntitle: Home\n---\n\n```rust {2}\nfn main() {\n    println!(\"<hi>\");\n}\n```\n",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_highlight_with_classes() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("highlight")?;
+    let project_path = tmp_dir.path();
+    create_project(
+        project_path,
+        "highlight:\n  theme: base16-ocean.dark\n  line_numbers: true\n",
+    )?;
+
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+    let output = project_path.join("output");
+
+    let index = fs::read_to_string(output.join("index.html"))?;
+    assert!(index.contains(r#"<pre class="hl-code"><code class="language-rust">"#));
+    assert!(index.contains(r#"<span class="line highlighted"><span class="line-number">2</span>"#));
+    assert!(index.contains("&lt;hi&gt;"));
+    let css = fs::read_to_string(output.join("highlight.css"))?;
+    assert!(css.contains(".hl-code"));
+    assert!(css.contains(".hl-code .highlighted"));
+    Ok(())
+}
+
+#[test]
+fn test_highlight_inline() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("highlight")?;
+    let project_path = tmp_dir.path();
+    create_project(project_path, "highlight:\n  style: inline\n")?;
+
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+    let output = project_path.join("output");
+
+    let index = fs::read_to_string(output.join("index.html"))?;
+    assert!(index.contains(r#"<pre style="background-color: #ffffffff;">"#));
+    assert!(index.contains(r#"<span style="color:"#));
+    assert!(!index.contains("line-number"));
+    assert!(!output.join("highlight.css").exists());
+    Ok(())
+}
+
+#[test]
+fn test_highlight_unknown_theme() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("highlight")?;
+    let project_path = tmp_dir.path();
+    create_project(project_path, "highlight:\n  theme: missing\n")?;
+
+    let error = genereto::run(project_path.into(), DraftsOptions::Build).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("Unknown highlight theme 'missing'"));
+    Ok(())
+}
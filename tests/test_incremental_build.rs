@@ -0,0 +1,254 @@
+use genereto::DraftsOptions;
+use std::fs::{self, File};
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+fn create_project(project_path: &Path) -> anyhow::Result<()> {
+    fs::create_dir_all(project_path.join("templates/default"))?;
+    fs::create_dir_all(project_path.join("content/blog"))?;
+    fs::write(
+        project_path.join("config.yml"),
+        r#"
+template: default
+title: Test Blog
+url: http://test.com
+description: Test blog description
+blog:
+  base_template: blog.html
+  index_name: blog.html
+  destination: blog
+"#,
+    )?;
+    let template = r#"<html><body><title>$GENERETO['title']</title>
+<!-- start_content -->
+<h2>$GENERETO['title']</h2>
+<!-- end_content -->
+</body></html>"#;
+    fs::write(project_path.join("templates/default/blog.html"), template)?;
+    fs::write(project_path.join("templates/default/index.html"), template)?;
+    for (name, title) in [("first-post", "First Post"), ("second-post", "Second Post")] {
+        fs::write(
+            project_path.join(format!("content/blog/{name}.md")),
+            format!("---\ntitle: {title}\npublish_date: '2024-01-01'\n---\n\nSome content.\n"),
+        )?;
+    }
+    Ok(())
+}
+
+fn modified(path: &Path) -> anyhow::Result<SystemTime> {
+    Ok(fs::metadata(path)?.modified()?)
+}
+
+/// Moves the modification time of a file in the past, to tell if the next build rewrites it.
+fn make_old(path: &Path) -> anyhow::Result<SystemTime> {
+    let old = SystemTime::now() - Duration::from_secs(3600);
+    File::options().write(true).open(path)?.set_modified(old)?;
+    modified(path)
+}
+
+#[test]
+fn test_incremental_build_only_rewrites_changed_pages() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("example")?;
+    let project_path = tmp_dir.path();
+    create_project(project_path)?;
+
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+    let output = project_path.join("output/blog");
+    let first_post = output.join("first-post.html");
+    let second_post = output.join("second-post.html");
+    let first_post_time = make_old(&first_post)?;
+    let second_post_time = make_old(&second_post)?;
+
+    fs::write(
+        project_path.join("content/blog/second-post.md"),
+        "---\ntitle: Second Post Edited\npublish_date: '2024-01-01'\n---\n\nSome content.\n",
+    )?;
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+
+    assert_eq!(modified(&first_post)?, first_post_time);
+    assert_ne!(modified(&second_post)?, second_post_time);
+    assert!(fs::read_to_string(&second_post)?.contains("Second Post Edited"));
+    let index = fs::read_to_string(output.join("blog.html"))?;
+    assert!(index.contains("First Post"));
+    assert!(index.contains("Second Post Edited"));
+
+    // Removing a source removes its output too.
+    fs::remove_file(project_path.join("content/blog/second-post.md"))?;
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+    assert!(first_post.exists());
+    assert!(!second_post.exists());
+    assert!(!fs::read_to_string(output.join("blog.html"))?.contains("Second Post"));
+
+    Ok(())
+}
+
+#[test]
+fn test_incremental_build_rebuilds_on_template_or_config_change() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("example")?;
+    let project_path = tmp_dir.path();
+    create_project(project_path)?;
+    let first_post = project_path.join("output/blog/first-post.html");
+
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+    let before = make_old(&first_post)?;
+    fs::write(
+        project_path.join("templates/default/blog.html"),
+        "<!-- start_content -->\n<!-- end_content -->\n<footer>New footer</footer>",
+    )?;
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+    assert_ne!(modified(&first_post)?, before);
+    assert!(fs::read_to_string(&first_post)?.contains("New footer"));
+
+    let before = make_old(&first_post)?;
+    let config = fs::read_to_string(project_path.join("config.yml"))?;
+    fs::write(
+        project_path.join("config.yml"),
+        config.replace("http://test.com", "http://other.com"),
+    )?;
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+    assert_ne!(modified(&first_post)?, before);
+
+    Ok(())
+}
+
+#[test]
+fn test_incremental_build_removes_deleted_assets() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("example")?;
+    let project_path = tmp_dir.path();
+    create_project(project_path)?;
+    fs::create_dir_all(project_path.join("templates/default/res"))?;
+    fs::create_dir_all(project_path.join("content/blog/first-post"))?;
+    let sources = [
+        "templates/default/res/style.css",
+        "content/image.png",
+        "content/blog/first-post/photo.jpg",
+    ];
+    for source in sources {
+        fs::write(project_path.join(source), "asset")?;
+    }
+    let output = project_path.join("output");
+    let outputs = [
+        output.join("res/style.css"),
+        output.join("image.png"),
+        output.join("blog/first-post/photo.jpg"),
+    ];
+
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+    assert!(outputs.iter().all(|output| output.exists()));
+
+    for source in sources {
+        fs::remove_file(project_path.join(source))?;
+    }
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+    for output in outputs {
+        assert!(!output.exists(), "{output:?}");
+    }
+    assert!(output.join("blog/first-post.html").exists());
+    Ok(())
+}
+
+#[test]
+fn test_build_to_external_output_dir_keeps_git_folder() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("example")?;
+    let project_path = tmp_dir.path().join("project");
+    let public = tmp_dir.path().join("public");
+    create_project(&project_path)?;
+    fs::create_dir_all(public.join(".git"))?;
+    fs::write(public.join(".git/HEAD"), "ref: refs/heads/gh-pages")?;
+    fs::write(public.join("stale.html"), "stale")?;
+    // A checkout of a previous build, written with another config.
+    fs::write(public.join(".genereto-cache.yml"), "config_hash: old\n")?;
+
+    let config =
+        genereto::GeneretoConfig::load_from_folder(&project_path)?.with_output_dir(&public)?;
+    genereto::build(&config, &DraftsOptions::Build)?;
+
+    assert!(public.join("blog/first-post.html").exists());
+    assert!(public.join(".git/HEAD").exists());
+    assert!(!public.join("stale.html").exists());
+    assert!(!project_path.join("output").exists());
+    Ok(())
+}
+
+#[test]
+fn test_output_dir_with_other_files_is_not_wiped() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("example")?;
+    let project_path = tmp_dir.path().join("project");
+    let public = tmp_dir.path().join("public");
+    create_project(&project_path)?;
+    fs::create_dir_all(&public)?;
+    fs::write(public.join("notes.txt"), "not a website")?;
+
+    let config =
+        genereto::GeneretoConfig::load_from_folder(&project_path)?.with_output_dir(&public)?;
+    let error = genereto::build(&config, &DraftsOptions::Build).unwrap_err();
+    assert!(error.to_string().contains(".genereto-cache.yml"), "{error}");
+    assert!(public.join("notes.txt").exists());
+
+    // An empty folder, or one with only a git checkout, is fine.
+    fs::remove_file(public.join("notes.txt"))?;
+    fs::create_dir_all(public.join(".git"))?;
+    genereto::build(&config, &DraftsOptions::Build)?;
+    assert!(public.join("blog/first-post.html").exists());
+    Ok(())
+}
+
+#[test]
+fn test_build_after_a_failed_first_build() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("example")?;
+    let project_path = tmp_dir.path();
+    create_project(project_path)?;
+    let template_path = project_path.join("templates/default/index.html");
+    let template = fs::read_to_string(&template_path)?;
+    fs::write(&template_path, "<html>No markers</html>")?;
+    fs::write(
+        project_path.join("content/about.md"),
+        "---\ntitle: About\n---\n\nAbout me.\n",
+    )?;
+    assert!(genereto::run(project_path.into(), DraftsOptions::Build).is_err());
+
+    // The output of the failed build is still recognized as genereto's.
+    fs::write(&template_path, template)?;
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+    assert!(project_path.join("output/about.html").exists());
+    Ok(())
+}
+
+#[test]
+fn test_incremental_build_sees_new_commits() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("example")?;
+    let project_path = tmp_dir.path();
+    create_project(project_path)?;
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+    let sitemap = project_path.join("output/sitemap.xml");
+    assert!(!fs::read_to_string(&sitemap)?.contains("2024-03-05"));
+
+    // Committing the unchanged source moves its last modified date.
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .current_dir(project_path)
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .env("GIT_COMMITTER_DATE", "2024-03-05T12:00:00Z")
+            .status()
+    };
+    if !git(&["init", "-q"]).is_ok_and(|status| status.success()) {
+        // git is not available.
+        return Ok(());
+    }
+    assert!(git(&["add", "content"])?.success());
+    assert!(git(&["commit", "-q", "-m", "Add content"])?.success());
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+
+    let sitemap = fs::read_to_string(&sitemap)?;
+    assert!(
+        sitemap.contains("<lastmod>2024-03-05</lastmod>"),
+        "{sitemap}"
+    );
+    // Pages content is kept in the manifest only if feeds or search need it.
+    let manifest = fs::read_to_string(project_path.join("output/.genereto-cache.yml"))?;
+    assert!(!manifest.contains("<p>Some content.</p>"));
+    Ok(())
+}
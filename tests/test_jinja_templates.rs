@@ -268,6 +268,8 @@ blog:
 <body>
 <p>Author: {{ page.author }}</p>
 <p>Category: {{ page.category }}</p>
+<p>Authors: {{ page.authors | join(" & ") }}</p>
+<p>{{ page.series.name }}, part {{ page.series.part + 1 }}</p>
 {{ content }}
 </body>
 </html>"#,
@@ -289,6 +291,10 @@ publish_date: 2024-01-15
 description: Testing custom fields
 author: John Doe
 category: Technology
+authors: [Ada, Grace]
+series:
+  name: Rust from scratch
+  part: 1
 ---
 
 Content here.
@@ -314,6 +320,9 @@ Content here.
         "Should contain category: {}",
         blog_post
     );
+    // Lists and maps keep their type.
+    assert!(blog_post.contains("Authors: Ada & Grace"));
+    assert!(blog_post.contains("Rust from scratch, part 2"));
 }
 
 #[test]
@@ -405,3 +414,156 @@ Content here.
         "Content should be rendered"
     );
 }
+
+fn create_inheritance_project(project_path: &std::path::Path) {
+    fs::create_dir_all(project_path.join("content/blog")).unwrap();
+    fs::create_dir_all(project_path.join("templates/main/partials")).unwrap();
+    fs::write(
+        project_path.join("config.yml"),
+        r#"
+template: main
+enable_jinja: true
+title: Test Jinja Site
+url: https://test.example.com
+blog:
+  base_template: blog-index.html
+  index_name: index.html
+  destination: blog
+"#,
+    )
+    .unwrap();
+    fs::write(
+        project_path.join("templates/main/base.html"),
+        r#"<html><head><title>{% block title %}{{ site.title }}{% endblock %}</title></head>
+<body>{% include "partials/nav.html" %}
+{% block body %}{% endblock %}</body></html>"#,
+    )
+    .unwrap();
+    fs::write(
+        project_path.join("templates/main/partials/nav.html"),
+        r#"<nav><a href="{{ site.url }}">Home</a></nav>"#,
+    )
+    .unwrap();
+    fs::write(
+        project_path.join("templates/main/macros.html"),
+        r#"{% macro card(article) %}<div class="card">{{ article.title }}</div>{% endmacro %}"#,
+    )
+    .unwrap();
+    fs::write(
+        project_path.join("templates/main/index.html"),
+        r#"{% extends "base.html" %}{% block title %}{{ page.title }}{% endblock %}
+{% block body %}<main>{{ content }}</main>{% endblock %}"#,
+    )
+    .unwrap();
+    fs::write(
+        project_path.join("templates/main/blog.html"),
+        r#"{% extends "base.html" %}{% block body %}<article>{{ content }}</article>{% endblock %}"#,
+    )
+    .unwrap();
+    fs::write(
+        project_path.join("templates/main/blog-index.html"),
+        r#"{% extends "base.html" %}{% import "macros.html" as macros %}
+{% block body %}{% for article in articles %}{{ macros.card(article) }}{% endfor %}{% endblock %}"#,
+    )
+    .unwrap();
+    fs::write(
+        project_path.join("content/index.md"),
+        "---\ntitle: Home\n---\n\nWelcome!\n",
+    )
+    .unwrap();
+    fs::write(
+        project_path.join("content/blog/2024-01-15-post.md"),
+        "---\ntitle: First Post\npublish_date: 2024-01-15\n---\n\nHello!\n",
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_jinja_template_inheritance() {
+    let temp_dir = tempdir().unwrap();
+    let project_path = temp_dir.path();
+    create_inheritance_project(project_path);
+
+    genereto::run(project_path.to_path_buf(), genereto::DraftsOptions::Build).unwrap();
+    let output = project_path.join("output");
+
+    let index = fs::read_to_string(output.join("index.html")).unwrap();
+    assert!(index.contains("<title>Home</title>"));
+    assert!(index.contains(r#"<nav><a href="https://test.example.com">Home</a></nav>"#));
+    assert!(index.contains("<main><p>Welcome!</p>\n</main>"));
+
+    let post = fs::read_to_string(output.join("blog/2024-01-15-post.html")).unwrap();
+    assert!(post.contains("<title>Test Jinja Site</title>"));
+    assert!(post.contains("<article><p>Hello!</p>\n</article>"));
+
+    let blog_index = fs::read_to_string(output.join("blog/index.html")).unwrap();
+    assert!(blog_index.contains(r#"<div class="card">First Post</div>"#));
+
+    // Pages are rebuilt when a template they extend changes.
+    fs::write(
+        project_path.join("templates/main/base.html"),
+        r#"<html><body class="new">{% block body %}{% endblock %}</body></html>"#,
+    )
+    .unwrap();
+    genereto::run(project_path.to_path_buf(), genereto::DraftsOptions::Build).unwrap();
+    let index = fs::read_to_string(output.join("index.html")).unwrap();
+    assert!(index.contains(r#"<body class="new"><main>"#));
+}
+
+#[test]
+fn test_jinja_errors_report_the_template() {
+    let temp_dir = tempdir().unwrap();
+    let project_path = temp_dir.path();
+    create_inheritance_project(project_path);
+    fs::write(
+        project_path.join("templates/main/base.html"),
+        "<html>\n<body>\n{% block body %}{% endblock %\n</body></html>",
+    )
+    .unwrap();
+
+    let error =
+        genereto::run(project_path.to_path_buf(), genereto::DraftsOptions::Build).unwrap_err();
+    let error = format!("{error:#}");
+    assert!(error.contains("base.html:3"), "got: {error}");
+}
+
+#[test]
+fn test_jinja_filters_and_functions() {
+    let temp_dir = tempdir().unwrap();
+    let project_path = temp_dir.path();
+    create_inheritance_project(project_path);
+    fs::create_dir_all(project_path.join("templates/main/res")).unwrap();
+    fs::write(project_path.join("templates/main/res/style.css"), "body {}").unwrap();
+    fs::write(
+        project_path.join("templates/main/blog.html"),
+        r#"<link href="{{ asset("res/style.css") }}">
+<time>{{ page.publish_date | date("%b %-d, %Y") }}</time>
+<a href="{{ page.title | slugify | relative_url }}">{{ page.description | truncate_words(1) }}</a>
+{{ page.subtitle | markdown }}"#,
+    )
+    .unwrap();
+    fs::write(
+        project_path.join("content/blog/2024-01-15-post.md"),
+        "---\ntitle: First Post\npublish_date: 2024-01-15\nsubtitle: A *short* one\n---\n\nHello world!\n",
+    )
+    .unwrap();
+
+    genereto::run(project_path.to_path_buf(), genereto::DraftsOptions::Build).unwrap();
+    let post_path = project_path.join("output/blog/2024-01-15-post.html");
+    let post = fs::read_to_string(&post_path).unwrap();
+    assert!(post.contains("<time>Jan 15, 2024</time>"));
+    assert!(post.contains(r#"<a href="/first-post">Hello...</a>"#));
+    assert!(post.contains("<p>A <em>short</em> one</p>"));
+    let asset = post.split('"').nth(1).unwrap().to_string();
+    assert!(asset.starts_with("https://test.example.com/res/style.css?v="));
+
+    // The fingerprint follows the content of the asset.
+    fs::write(
+        project_path.join("templates/main/res/style.css"),
+        "body { margin: 0 }",
+    )
+    .unwrap();
+    genereto::run(project_path.to_path_buf(), genereto::DraftsOptions::Build).unwrap();
+    let post = fs::read_to_string(&post_path).unwrap();
+    assert!(!post.contains(&asset));
+}
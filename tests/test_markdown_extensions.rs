@@ -0,0 +1,52 @@
+use genereto::DraftsOptions;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn create_project(project_path: &Path) -> anyhow::Result<()> {
+    fs::create_dir_all(project_path.join("templates/default"))?;
+    fs::create_dir_all(project_path.join("content"))?;
+    fs::write(
+        project_path.join("config.yml"),
+        "template: default\ntitle: Test\nurl: http://test.com\nmarkdown:\n  footnotes: true\n  tasklists: true\n  smart_punctuation: true\n",
+    )?;
+    fs::write(
+        project_path.join("templates/default/index.html"),
+        "<html><head><meta name=\"description\" content=\"$GENERETO['description']\"></head><body>$GENERETO['table_of_contents']<!-- start_content -->\n<!-- end_content --></body></html>",
+    )?;
+    fs::write(
+        project_path.join("content/index.md"),
+        "---\ntitle: Home\nshow_table_of_contents: true\n---\n\n## It's \"done\"\n\nSee the note.[^1]\n\n- [x] first task\n\n```\n# not a heading\n```\n\n[^1]: The footnote.\n",
+    )?;
+    fs::write(
+        project_path.join("content/about.md"),
+        "---\ntitle: About\nmarkdown:\n  smart_punctuation: false\n  definition_lists: true\n---\n\nIt's \"plain\".\n\nTerm\n: Definition\n",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_markdown_extensions_from_config_and_frontmatter() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("markdown_extensions")?;
+    let project_path = tmp_dir.path();
+    create_project(project_path)?;
+
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+    let output = project_path.join("output");
+
+    let index = fs::read_to_string(output.join("index.html"))?;
+    assert!(index.contains(r##"<sup class="footnote-reference"><a href="#1">1</a></sup>"##));
+    assert!(index.contains(r#"<input disabled="" type="checkbox" checked=""/>"#));
+    // The ToC and the description follow the enabled extensions.
+    assert!(index.contains("class=\"table_of_contents-indent-2\">It’s “done”</a>"));
+    assert!(!index.contains("not a heading</a>"));
+    let description = index.split("content=\"").nth(1).unwrap().split('"').next();
+    assert!(description.unwrap().starts_with("See the note.first task"));
+    assert!(!description.unwrap().contains("The footnote."));
+
+    let about = fs::read_to_string(output.join("about.html"))?;
+    assert!(about.contains(r#"<p>It's "plain".</p>"#));
+    assert!(about.contains("<dt>Term</dt>"));
+    assert!(about.contains("<dd>Definition</dd>"));
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use std::fs;
+use tempfile::tempdir;
+
+/// Markdown files in nested content folders are compiled, mirroring the folder structure.
+#[test]
+fn test_nested_pages_are_compiled() {
+    let temp_dir = tempdir().unwrap();
+    let project_path = temp_dir.path();
+
+    fs::create_dir_all(project_path.join("content/docs/guides/setup")).unwrap();
+    fs::create_dir_all(project_path.join("templates/main")).unwrap();
+
+    fs::write(
+        project_path.join("config.yml"),
+        r#"
+template: main
+title: Test Site
+url: https://test.example.com
+description: A test site
+"#,
+    )
+    .unwrap();
+
+    fs::write(
+        project_path.join("templates/main/index.html"),
+        r#"<html><body>
+<!-- start_content -->
+placeholder
+<!-- end_content -->
+</body></html>"#,
+    )
+    .unwrap();
+
+    fs::write(
+        project_path.join("content/index.md"),
+        "---\ntitle: Home\n---\n\n[Install](docs/install.html)\n",
+    )
+    .unwrap();
+    fs::write(
+        project_path.join("content/docs/install.md"),
+        "---\ntitle: Install\n---\n\n[Setup](guides/setup.html)\n",
+    )
+    .unwrap();
+    fs::write(
+        project_path.join("content/docs/guides/setup.md"),
+        "---\ntitle: Setup\n---\n\n![Screenshot]($GENERETO['page_name']/screen.png)\n",
+    )
+    .unwrap();
+    fs::write(
+        project_path.join("content/docs/guides/setup/screen.png"),
+        "fake png",
+    )
+    .unwrap();
+    fs::write(project_path.join("content/docs/manual.pdf"), "fake pdf").unwrap();
+
+    genereto::run(project_path.to_path_buf(), genereto::DraftsOptions::Build).unwrap();
+    let output = project_path.join("output");
+
+    assert!(output.join("index.html").exists());
+    let install = fs::read_to_string(output.join("docs/install.html")).unwrap();
+    assert!(install.contains(r#"href="guides/setup.html""#));
+    assert!(!output.join("docs/install.md").exists());
+
+    let setup = fs::read_to_string(output.join("docs/guides/setup.html")).unwrap();
+    assert!(
+        setup.contains(r#"src="setup/screen.png""#),
+        "page_name should resolve next to the nested page, got: {setup}"
+    );
+    assert!(output.join("docs/guides/setup/screen.png").exists());
+    assert!(output.join("docs/manual.pdf").exists());
+}
@@ -0,0 +1,85 @@
+use genereto::verify::{self, Check};
+use genereto::{ContentKind, GeneretoConfig};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn create_project(project_path: &Path) -> anyhow::Result<GeneretoConfig> {
+    fs::create_dir_all(project_path.join("content"))?;
+    fs::write(
+        project_path.join("config.yml"),
+        "template: default\ntitle: Test Blog\nurl: http://test.com\n",
+    )?;
+    GeneretoConfig::load_from_folder(project_path)
+}
+
+#[test]
+fn test_new_post_from_default_archetype() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("new_content")?;
+    let config = create_project(tmp_dir.path())?;
+
+    let path = genereto::new_content(&config, ContentKind::Post, "My First Title!", true)?;
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    assert_eq!(
+        path,
+        config
+            .content_path
+            .join(format!("blog/{today}-my-first-title.md"))
+    );
+    assert_eq!(
+        fs::read_to_string(&path)?,
+        format!("---\ntitle: \"My First Title!\"\npublish_date: {today}\nkeywords: \"\"\n---\n\n")
+    );
+    assert!(config
+        .content_path
+        .join(format!("blog/{today}-my-first-title"))
+        .is_dir());
+
+    // The date of the file name and of the frontmatter agree.
+    let issues = verify::run_checks(&config, &[Check::DateMismatch], &config.output_dir_path);
+    assert!(issues.is_empty(), "{issues:?}");
+
+    // Existing files are not overwritten.
+    assert!(genereto::new_content(&config, ContentKind::Post, "My First Title", false).is_err());
+    assert!(genereto::new_content(&config, ContentKind::Post, "???", false).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_new_page_from_project_archetype() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("new_content")?;
+    let config = create_project(tmp_dir.path())?;
+    fs::create_dir_all(tmp_dir.path().join("archetypes"))?;
+    fs::write(
+        tmp_dir.path().join("archetypes/page.md"),
+        "---\ntitle: $GENERETO['title']\nsitemap: false\n---\n\n![]($GENERETO['page_name']/photo.jpg)\n",
+    )?;
+
+    let path = genereto::new_content(&config, ContentKind::Page, "about", false)?;
+    assert_eq!(path, config.content_path.join("about.md"));
+    assert_eq!(
+        fs::read_to_string(&path)?,
+        "---\ntitle: about\nsitemap: false\n---\n\n![](about/photo.jpg)\n"
+    );
+    assert!(!config.content_path.join("about").exists());
+    Ok(())
+}
+
+#[test]
+fn test_new_post_with_quotes_in_the_title() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("new_content")?;
+    let config = create_project(tmp_dir.path())?;
+
+    let title = r#"Say "hi" to C:\temp"#;
+    let path = genereto::new_content(&config, ContentKind::Post, title, false)?;
+    let content = fs::read_to_string(&path)?;
+    assert!(
+        content.contains(r#"title: "Say \"hi\" to C:\\temp""#),
+        "{content}"
+    );
+
+    let frontmatter = content.split("---").nth(1).unwrap();
+    let metadata: serde_yaml_ng::Value = serde_yaml_ng::from_str(frontmatter)?;
+    assert_eq!(metadata["title"].as_str(), Some(title));
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use genereto::DraftsOptions;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn create_project(project_path: &Path) -> anyhow::Result<()> {
+    fs::create_dir_all(project_path.join("templates/default"))?;
+    fs::create_dir_all(project_path.join("content/blog"))?;
+    fs::write(
+        project_path.join("config.yml"),
+        r#"
+template: default
+title: Test Blog
+url: http://test.com
+description: Test blog description
+blog:
+  base_template: blog.html
+  index_name: blog.html
+  destination: blog
+  max_entries_per_page: 7
+"#,
+    )?;
+    let template = r#"<html><body><title>$GENERETO['title']</title>
+<!-- start_content -->
+<h2><a href="$GENERETO['file_name']">$GENERETO['title']</a></h2>
+<!-- end_content -->
+$GENERETO['pagination']
+</body></html>"#;
+    fs::write(project_path.join("templates/default/blog.html"), template)?;
+    fs::write(project_path.join("templates/default/index.html"), template)?;
+    for i in 0..40 {
+        // Several posts share the same publish date, so their relative order must be stable.
+        fs::write(
+            project_path.join(format!("content/blog/post-{i:02}.md")),
+            format!(
+                "---\ntitle: Post {i}\npublish_date: '2024-01-{:02}'\n---\n\nContent of post {i}.\n",
+                i % 5 + 1
+            ),
+        )?;
+    }
+    for i in 0..10 {
+        fs::write(
+            project_path.join(format!("content/page-{i}.md")),
+            format!("---\ntitle: Page {i}\n---\n\nContent of page {i}.\n"),
+        )?;
+    }
+    Ok(())
+}
+
+fn read_output(
+    dir: &Path,
+    root: &Path,
+    files: &mut BTreeMap<String, Vec<u8>>,
+) -> anyhow::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            read_output(&path, root, files)?;
+        } else {
+            let relative = path.strip_prefix(root)?.display().to_string();
+            files.insert(relative, fs::read(&path)?);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_parallel_build_output_is_deterministic() -> anyhow::Result<()> {
+    let mut outputs = vec![];
+    for _ in 0..3 {
+        let tmp_dir = TempDir::with_prefix("example")?;
+        let project_path = tmp_dir.path();
+        create_project(project_path)?;
+        let output_dir = genereto::run(project_path.into(), DraftsOptions::Build)?;
+        let mut files = BTreeMap::new();
+        read_output(&output_dir, &output_dir, &mut files)?;
+        // The cache manifest contains absolute paths of the temporary project.
+        files.remove(".genereto-cache.yml");
+        outputs.push(files);
+    }
+    // Articles, pages, blog index pages, rss.xml, sitemap.xml and robots.txt.
+    assert_eq!(outputs[0].len(), 40 + 10 + 6 + 1 + 2);
+    assert_eq!(outputs[0], outputs[1]);
+    assert_eq!(outputs[0], outputs[2]);
+    Ok(())
+}
@@ -23,12 +23,21 @@ fn test_generate_project_with_override_git() {
     assert!(project_path.join("genereto-project").exists());
     assert!(project_path.join("genereto-project/templates").exists());
     assert!(project_path.join("genereto-project/config.yml").exists());
+    assert!(project_path
+        .join("genereto-project/templates/main/res/search.js")
+        .exists());
+    assert!(project_path
+        .join("genereto-project/templates/main/shortcodes/note.html")
+        .exists());
+    assert!(project_path
+        .join("genereto-project/archetypes/post.md")
+        .exists());
 }
 
 #[test]
 #[ignore]
 fn test_blog_generation_with_single_pages_disabled() {
-    use genereto::{GeneretoConfig, GeneretoConfigBlog};
+    use genereto::{FeedFormat, GeneretoConfig, GeneretoConfigBlog};
     use std::fs;
     use std::path::PathBuf;
 
@@ -75,6 +84,19 @@ fn test_blog_generation_with_single_pages_disabled() {
             title: None,
             max_entries_per_page: None,
         },
+        taxonomies: vec![],
+        feeds: vec![FeedFormat::Rss],
+        language: "en-us".into(),
+        author: None,
+        feed_full_content: false,
+        sitemap: true,
+        robots_txt: None,
+        search_index: false,
+        highlight: None,
+        markdown: Default::default(),
+        timezone: None,
+        expired_template: None,
+        template_dirs: vec![project_path.join("templates/main")],
     };
 
     // Run blog generation
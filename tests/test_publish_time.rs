@@ -0,0 +1,94 @@
+use genereto::DraftsOptions;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn create_project(project_path: &Path, timezone: &str) -> anyhow::Result<()> {
+    fs::create_dir_all(project_path.join("templates/default"))?;
+    fs::create_dir_all(project_path.join("content/blog"))?;
+    fs::write(
+        project_path.join("config.yml"),
+        format!(
+            r#"
+template: default
+title: Test Blog
+url: http://test.com
+description: Test blog description
+timezone: {timezone}
+blog:
+  destination: blog
+  generate_single_pages: true
+feeds: [rss, json]
+"#
+        ),
+    )?;
+    fs::write(
+        project_path.join("templates/default/index.html"),
+        "<html><body><!-- start_content -->\n<!-- end_content --></body></html>",
+    )?;
+    fs::write(
+        project_path.join("templates/default/blog.html"),
+        "<time datetime=\"$GENERETO['publish_datetime']\">$GENERETO['publish_date']</time><!-- start_content -->\n<!-- end_content -->",
+    )?;
+    for (name, date) in [
+        ("morning-post", "2024-01-15 08:00"),
+        ("evening-post", "2024-01-15T18:00:00Z"),
+        ("day-post", "2024-01-15"),
+    ] {
+        fs::write(
+            project_path.join(format!("content/blog/{name}.md")),
+            format!("---\ntitle: {name}\npublish_date: '{date}'\n---\n\nContent.\n"),
+        )?;
+    }
+    let in_an_hour = chrono::Utc::now() + chrono::Duration::hours(1);
+    fs::write(
+        project_path.join("content/blog/scheduled-post.md"),
+        format!(
+            "---\ntitle: scheduled-post\npublish_date: '{}'\n---\n\nContent.\n",
+            in_an_hour.to_rfc3339()
+        ),
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_publish_time_with_timezone() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("publish_time")?;
+    let project_path = tmp_dir.path();
+    create_project(project_path, "Europe/Rome")?;
+
+    genereto::run(project_path.into(), DraftsOptions::Hide)?;
+    let output = project_path.join("output");
+
+    let morning = fs::read_to_string(output.join("blog/morning-post.html"))?;
+    assert!(
+        morning.contains(r#"<time datetime="2024-01-15T08:00:00+01:00">2024-01-15 08:00</time>"#)
+    );
+    // Scheduled later today, so it's a draft for now.
+    assert!(!output.join("blog/scheduled-post.html").exists());
+
+    let rss = fs::read_to_string(output.join("rss.xml"))?;
+    assert!(rss.contains("<pubDate>Mon, 15 Jan 2024 08:00:00 +0100</pubDate>"));
+    assert!(rss.contains("<pubDate>Mon, 15 Jan 2024 18:00:00 +0000</pubDate>"));
+    // Newest first: 18:00 UTC, then 08:00 in Rome, then midnight in Rome.
+    let evening = rss.find("evening-post").unwrap();
+    let morning = rss.find("morning-post").unwrap();
+    let day = rss.find("day-post").unwrap();
+    assert!(evening < morning && morning < day);
+    assert!(!rss.contains("scheduled-post"));
+
+    let json_feed = fs::read_to_string(output.join("feed.json"))?;
+    assert!(json_feed.contains(r#""date_published": "2024-01-15T00:00:00+01:00""#));
+    Ok(())
+}
+
+#[test]
+fn test_unknown_timezone() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("publish_time")?;
+    let project_path = tmp_dir.path();
+    create_project(project_path, "Mars/Olympus_Mons")?;
+
+    let error = genereto::run(project_path.into(), DraftsOptions::Build).unwrap_err();
+    assert!(format!("{error:#}").contains("Unknown timezone 'Mars/Olympus_Mons'"));
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use genereto::DraftsOptions;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_search_index() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("search")?;
+    let project_path = tmp_dir.path();
+    fs::create_dir_all(project_path.join("templates/default"))?;
+    fs::create_dir_all(project_path.join("content/blog"))?;
+    fs::write(
+        project_path.join("config.yml"),
+        r#"
+template: default
+title: Test Blog
+url: http://test.com
+search_index: true
+blog:
+  destination: blog
+"#,
+    )?;
+    let template = "<html><body><!-- start_content -->\n<!-- end_content --></body></html>";
+    fs::write(project_path.join("templates/default/index.html"), template)?;
+    fs::write(project_path.join("templates/default/blog.html"), template)?;
+    fs::write(
+        project_path.join("content/blog/first-post.md"),
+        "---\ntitle: First Post\npublish_date: '2024-01-01'\nkeywords: rust\ndescription: The first one\n---\n\n## Intro\nSome *searchable* content.\n",
+    )?;
+    fs::write(
+        project_path.join("content/blog/draft-post.md"),
+        "---\ntitle: Draft Post\npublish_date: '2024-01-02'\nis_draft: true\n---\n\nDraft.\n",
+    )?;
+    fs::write(
+        project_path.join("content/about.md"),
+        "---\ntitle: About\n---\n\nAbout me.\n",
+    )?;
+
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+
+    let index: serde_json::Value = serde_json::from_str(&fs::read_to_string(
+        project_path.join("output/search-index.json"),
+    )?)?;
+    let entries = index.as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["url"], "about.html");
+    assert_eq!(entries[1]["title"], "First Post");
+    assert_eq!(entries[1]["url"], "blog/first-post.html");
+    assert_eq!(entries[1]["description"], "The first one");
+    assert_eq!(entries[1]["keywords"], "rust");
+    assert_eq!(entries[1]["body"], "Intro Some searchable content.");
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use genereto::DraftsOptions;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const PAGE: &str = "---\ntitle: Home\n---\n\n{{< youtube id=\"abc123\" >}}\n\n{{< note kind=warning >}}\nSome **bold** advice.\n{{< /note >}}\n\n```\n{{< youtube id=\"raw\" >}}\n```\n";
+
+fn create_project(project_path: &Path, config: &str) -> anyhow::Result<()> {
+    fs::create_dir_all(project_path.join("templates/default/shortcodes"))?;
+    fs::create_dir_all(project_path.join("content"))?;
+    fs::write(
+        project_path.join("config.yml"),
+        format!("template: default\ntitle: Test\nurl: http://test.com\n{config}"),
+    )?;
+    fs::write(project_path.join("content/index.md"), PAGE)?;
+    Ok(())
+}
+
+#[test]
+fn test_shortcodes_with_markers() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("shortcodes")?;
+    let project_path = tmp_dir.path();
+    create_project(project_path, "")?;
+    let template_path = project_path.join("templates/default");
+    fs::write(
+        template_path.join("index.html"),
+        "<html><body><!-- start_content -->\n<!-- end_content --></body></html>",
+    )?;
+    fs::write(
+        template_path.join("shortcodes/youtube.html"),
+        "<iframe src=\"https://www.youtube.com/embed/$GENERETO['id']\" title=\"$GENERETO['title']\"></iframe>\n",
+    )?;
+    fs::write(
+        template_path.join("shortcodes/note.html"),
+        "<div class=\"$GENERETO['kind']\">\n\n$GENERETO['body']\n\n</div>\n",
+    )?;
+
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+
+    let index = fs::read_to_string(project_path.join("output/index.html"))?;
+    // Markers which are not arguments are replaced with the ones of the page.
+    assert!(index
+        .contains(r#"<iframe src="https://www.youtube.com/embed/abc123" title="Home"></iframe>"#));
+    assert!(index
+        .contains("<div class=\"warning\">\n<p>Some <strong>bold</strong> advice.</p>\n</div>"));
+    assert!(index.contains(r#"<code>{{&lt; youtube id="raw" &gt;}}"#));
+    assert!(!project_path.join("output/shortcodes").exists());
+
+    // Pages are rebuilt when a shortcode changes.
+    fs::write(
+        template_path.join("shortcodes/note.html"),
+        "<aside>\n\n$GENERETO['body']\n\n</aside>\n",
+    )?;
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+    let index = fs::read_to_string(project_path.join("output/index.html"))?;
+    assert!(index.contains("<aside>\n<p>Some <strong>bold</strong> advice.</p>\n</aside>"));
+    Ok(())
+}
+
+#[test]
+fn test_shortcodes_with_jinja() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("shortcodes")?;
+    let project_path = tmp_dir.path();
+    create_project(project_path, "enable_jinja: true\n")?;
+    let template_path = project_path.join("templates/default");
+    fs::write(template_path.join("index.html"), "{{ content }}")?;
+    fs::write(
+        template_path.join("shortcodes/youtube.html"),
+        "<iframe src=\"https://www.youtube.com/embed/{{ id }}\" title=\"{{ page.title }} - {{ site.title }}\"></iframe>",
+    )?;
+    fs::write(
+        template_path.join("shortcodes/note.html"),
+        "<div class=\"{{ kind | default('info') }}\">\n\n{{ body }}\n\n</div>",
+    )?;
+
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+
+    let index = fs::read_to_string(project_path.join("output/index.html"))?;
+    assert!(index.contains(
+        r#"<iframe src="https://www.youtube.com/embed/abc123" title="Home - Test"></iframe>"#
+    ));
+    assert!(index
+        .contains("<div class=\"warning\">\n<p>Some <strong>bold</strong> advice.</p>\n</div>"));
+    assert!(index.contains(r#"<code>{{&lt; youtube id="raw" &gt;}}"#));
+    Ok(())
+}
+
+#[test]
+fn test_unknown_shortcode_fails_the_build() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("shortcodes")?;
+    let project_path = tmp_dir.path();
+    create_project(project_path, "")?;
+    fs::write(
+        project_path.join("templates/default/index.html"),
+        "<html><body><!-- start_content -->\n<!-- end_content --></body></html>",
+    )?;
+
+    let error = genereto::run(project_path.into(), DraftsOptions::Build).unwrap_err();
+    assert!(format!("{error:#}").contains("Unknown shortcode 'youtube'"));
+    Ok(())
+}
+
+#[test]
+fn test_sites_without_shortcodes_keep_the_text() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("shortcodes")?;
+    let project_path = tmp_dir.path();
+    create_project(project_path, "")?;
+    fs::remove_dir(project_path.join("templates/default/shortcodes"))?;
+    fs::write(
+        project_path.join("templates/default/index.html"),
+        "<html><body><!-- start_content -->\n<!-- end_content --></body></html>",
+    )?;
+    fs::write(
+        project_path.join("content/index.md"),
+        "---\ntitle: Home\n---\n\nEmbed videos with `{{< youtube id=1 >}}`.\n",
+    )?;
+
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+
+    let index = fs::read_to_string(project_path.join("output/index.html"))?;
+    assert!(index.contains("<code>{{&lt; youtube id=1 &gt;}}</code>"));
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use genereto::DraftsOptions;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const BLOG_TEMPLATE: &str = r#"<h1>{{ page.title }}</h1>
+{% if page.prev %}<a class="prev" href="{{ site.url }}/{{ page.prev.path }}">{{ page.prev.title }}</a>{% endif %}
+{% if page.next %}<a class="next" href="{{ site.url }}/{{ page.next.path }}">{{ page.next.title }}</a>{% endif %}
+{% for related in page.related %}<a class="related" href="{{ related.path }}">{{ related.title }}</a>
+{% endfor %}"#;
+
+const PAGE_TEMPLATE: &str = r#"<nav>{% for nav_page in site.pages %}<a href="{{ nav_page.path }}">{{ nav_page.title }}</a>{% endfor %}</nav>
+<ul>{% for article in site.articles[:2] %}<li>{{ article.title }}</li>{% endfor %}</ul>
+<p>{% for term in site.taxonomies.tags.terms %}{{ term.name }} ({{ term.count }}) {% endfor %}</p>
+{{ content }}"#;
+
+fn create_project(project_path: &Path) -> anyhow::Result<()> {
+    fs::create_dir_all(project_path.join("templates/default"))?;
+    fs::create_dir_all(project_path.join("content/blog"))?;
+    fs::write(
+        project_path.join("config.yml"),
+        r#"
+template: default
+title: Test Blog
+url: http://test.com
+enable_jinja: true
+blog:
+  base_template: blog-index.html
+  destination: blog
+  generate_single_pages: true
+taxonomies:
+  - name: tags
+"#,
+    )?;
+    fs::write(
+        project_path.join("templates/default/blog.html"),
+        BLOG_TEMPLATE,
+    )?;
+    fs::write(
+        project_path.join("templates/default/index.html"),
+        PAGE_TEMPLATE,
+    )?;
+    fs::write(
+        project_path.join("templates/default/blog-index.html"),
+        "{% for article in articles %}{{ article.title }}{% endfor %}",
+    )?;
+    fs::write(project_path.join("templates/default/tag.html"), "")?;
+    for (name, date, tags) in [
+        ("first-post", "2024-01-01", "[rust]"),
+        ("second-post", "2024-01-02", "[cooking]"),
+        ("third-post", "2024-01-03", "[rust, web]"),
+    ] {
+        fs::write(
+            project_path.join(format!("content/blog/{name}.md")),
+            format!("---\ntitle: {name}\npublish_date: '{date}'\ntags: {tags}\n---\n\nContent.\n"),
+        )?;
+    }
+    fs::write(
+        project_path.join("content/blog/draft-post.md"),
+        "---\ntitle: draft-post\npublish_date: '2024-01-04'\nis_draft: true\ntags: [rust]\n---\n\nDraft.\n",
+    )?;
+    fs::write(
+        project_path.join("content/index.md"),
+        "---\ntitle: Home\n---\n\nWelcome.\n",
+    )?;
+    fs::write(
+        project_path.join("content/about.md"),
+        "---\ntitle: About\ntags: [web]\n---\n\nAbout me.\n",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_site_collections_in_jinja_templates() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("site_collections")?;
+    let project_path = tmp_dir.path();
+    create_project(project_path)?;
+
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+    let output = project_path.join("output");
+
+    let index = fs::read_to_string(output.join("index.html"))?;
+    assert!(
+        index.contains(r#"<nav><a href="about.html">About</a><a href="index.html">Home</a></nav>"#)
+    );
+    // Articles are sorted like in the blog index, drafts are not listed.
+    assert!(index.contains("<ul><li>third-post</li><li>second-post</li></ul>"));
+    assert!(index.contains("<p>cooking (1) rust (2) web (2) </p>"));
+
+    let second = fs::read_to_string(output.join("blog/second-post.html"))?;
+    assert!(second
+        .contains(r#"<a class="prev" href="http://test.com/blog/first-post.html">first-post</a>"#));
+    assert!(second
+        .contains(r#"<a class="next" href="http://test.com/blog/third-post.html">third-post</a>"#));
+    assert!(!second.contains("class=\"related\""));
+
+    let third = fs::read_to_string(output.join("blog/third-post.html"))?;
+    assert!(!third.contains("class=\"next\""));
+    assert!(third.contains(
+        "<a class=\"related\" href=\"blog/first-post.html\">first-post</a>\n<a class=\"related\" href=\"about.html\">About</a>"
+    ));
+
+    // Every page lists the others, so a new article rebuilds all of them.
+    fs::write(
+        project_path.join("content/blog/fourth-post.md"),
+        "---\ntitle: fourth-post\npublish_date: '2024-01-05'\n---\n\nContent.\n",
+    )?;
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+    let index = fs::read_to_string(output.join("index.html"))?;
+    assert!(index.contains("<ul><li>fourth-post</li><li>third-post</li></ul>"));
+    let third = fs::read_to_string(output.join("blog/third-post.html"))?;
+    assert!(third.contains(r#"<a class="next" href="http://test.com/blog/fourth-post.html">"#));
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use genereto::DraftsOptions;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn create_project(project_path: &Path, config: &str) -> anyhow::Result<()> {
+    fs::create_dir_all(project_path.join("templates/default"))?;
+    fs::create_dir_all(project_path.join("content/blog"))?;
+    fs::write(project_path.join("config.yml"), config)?;
+    let template = "<html><body><!-- start_content -->\n<!-- end_content --></body></html>";
+    fs::write(project_path.join("templates/default/index.html"), template)?;
+    fs::write(project_path.join("templates/default/blog.html"), template)?;
+    for (name, date) in [
+        ("first-post", "2024-01-01"),
+        ("second-post", "2024-01-02"),
+        ("third-post", "2024-01-03"),
+    ] {
+        fs::write(
+            project_path.join(format!("content/blog/{name}.md")),
+            format!("---\ntitle: {name}\npublish_date: '{date}'\n---\n\nSome content.\n"),
+        )?;
+    }
+    fs::write(
+        project_path.join("content/blog/draft-post.md"),
+        "---\ntitle: draft-post\npublish_date: '2024-01-04'\nis_draft: true\n---\n\nDraft.\n",
+    )?;
+    fs::write(
+        project_path.join("content/about.md"),
+        "---\ntitle: About\npriority: 0.8\n---\n\nAbout me.\n",
+    )?;
+    fs::write(
+        project_path.join("content/thanks.md"),
+        "---\ntitle: Thanks\nsitemap: false\n---\n\nThanks.\n",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_sitemap_and_robots_txt() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("sitemap")?;
+    let project_path = tmp_dir.path();
+    create_project(
+        project_path,
+        r#"
+template: default
+title: Test Blog
+url: http://test.com
+blog:
+  destination: blog
+  max_entries_per_page: 2
+robots_txt: |
+  User-agent: *
+  Disallow: /private/
+"#,
+    )?;
+
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+    let output = project_path.join("output");
+
+    let sitemap = fs::read_to_string(output.join("sitemap.xml"))?;
+    assert!(sitemap.contains(
+        "<loc>http://test.com/blog/first-post.html</loc>\n    <lastmod>2024-01-01</lastmod>"
+    ));
+    assert!(sitemap.contains("<loc>http://test.com/about.html</loc>\n    <priority>0.8</priority>"));
+    assert!(sitemap.contains("<loc>http://test.com/blog/index.html</loc>"));
+    assert!(sitemap.contains("<loc>http://test.com/blog/index-page-2.html</loc>"));
+    assert!(!sitemap.contains("draft-post"));
+    assert!(!sitemap.contains("thanks.html"));
+    assert!(output.join("thanks.html").exists());
+
+    let robots = fs::read_to_string(output.join("robots.txt"))?;
+    assert_eq!(
+        robots,
+        "User-agent: *\nDisallow: /private/\n\nSitemap: http://test.com/sitemap.xml\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_sitemap_disabled() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("sitemap")?;
+    let project_path = tmp_dir.path();
+    create_project(
+        project_path,
+        r#"
+template: default
+title: Test Blog
+url: http://test.com
+sitemap: false
+"#,
+    )?;
+
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+    let output = project_path.join("output");
+
+    assert!(!output.join("sitemap.xml").exists());
+    assert_eq!(
+        fs::read_to_string(output.join("robots.txt"))?,
+        "User-agent: *\nAllow: /\n"
+    );
+    Ok(())
+}
@@ -0,0 +1,188 @@
+use genereto::DraftsOptions;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn create_project(project_path: &Path, config: &str, blog_template: &str) -> anyhow::Result<()> {
+    fs::create_dir_all(project_path.join("templates/default"))?;
+    fs::create_dir_all(project_path.join("content/blog"))?;
+    fs::write(project_path.join("config.yml"), config)?;
+    fs::write(
+        project_path.join("templates/default/index.html"),
+        "<html><body><!-- start_content -->\n<!-- end_content --></body></html>",
+    )?;
+    fs::write(
+        project_path.join("templates/default/blog.html"),
+        "<html><body><!-- start_content -->\n<!-- end_content --></body></html>",
+    )?;
+    fs::write(
+        project_path.join("templates/default/blog-index.html"),
+        blog_template,
+    )?;
+    for (name, date, tags) in [
+        ("first-post", "2024-01-01", "[Rust, web]"),
+        ("second-post", "2024-01-02", "[rust]"),
+        ("third-post", "2024-01-03", "[Rust]"),
+    ] {
+        fs::write(
+            project_path.join(format!("content/blog/{name}.md")),
+            format!(
+                "---\ntitle: {name}\npublish_date: '{date}'\ntags: {tags}\ncategories: notes\n---\n\nSome content.\n"
+            ),
+        )?;
+    }
+    fs::write(
+        project_path.join("content/blog/draft-post.md"),
+        "---\ntitle: draft-post\npublish_date: '2024-01-04'\nis_draft: true\ntags: [rust]\n---\n\nDraft.\n",
+    )?;
+    fs::write(
+        project_path.join("content/about.md"),
+        "---\ntitle: About\ntags: [web]\n---\n\nAbout me.\n",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_taxonomy_pages_with_markers() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("taxonomies")?;
+    let project_path = tmp_dir.path();
+    create_project(
+        project_path,
+        r#"
+template: default
+title: Test Blog
+url: http://test.com
+blog:
+  base_template: blog-index.html
+  index_name: index.html
+  destination: blog
+  max_entries_per_page: 2
+taxonomies:
+  - name: tags
+  - name: categories
+"#,
+        r#"<html><title>$GENERETO['title']</title><body><!-- start_content -->
+<a href="$GENERETO['file_name']">$GENERETO['title'] $GENERETO['term_count']</a>
+<!-- end_content -->$GENERETO['pagination']</body></html>"#,
+    )?;
+
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+    let output = project_path.join("output");
+
+    let rust = fs::read_to_string(output.join("tags/rust/index.html"))?;
+    assert!(rust.contains("<title>Rust</title>"));
+    assert!(rust.contains(r#"<a href="../../blog/third-post.html">third-post"#));
+    assert!(rust.contains(r#"<a href="../../blog/second-post.html">second-post"#));
+    assert!(rust.contains("Page 1 of 2"));
+    let rust_page_2 = fs::read_to_string(output.join("tags/rust/index-page-2.html"))?;
+    assert!(rust_page_2.contains(r#"<a href="../../blog/first-post.html">first-post"#));
+
+    // Regular pages are part of the taxonomies too.
+    let web = fs::read_to_string(output.join("tags/web/index.html"))?;
+    assert!(web.contains(r#"<a href="../../about.html">About"#));
+    assert!(web.contains(r#"<a href="../../blog/first-post.html">first-post"#));
+
+    let tags = fs::read_to_string(output.join("tags/index.html"))?;
+    assert!(tags.contains("<title>tags</title>"));
+    assert!(tags.contains(r#"<a href="rust/index.html">Rust 3</a>"#));
+    assert!(tags.contains(r#"<a href="web/index.html">web 2</a>"#));
+
+    assert!(output.join("categories/notes/index.html").exists());
+    assert!(output.join("categories/index.html").exists());
+
+    let rust_feed = fs::read_to_string(output.join("tags/rust/rss.xml"))?;
+    assert!(rust_feed.contains("<title>Test Blog - Rust</title>"));
+    assert!(rust_feed.contains("<link>http://test.com/blog/first-post.html</link>"));
+    assert!(rust_feed.contains("<link>http://test.com/blog/third-post.html</link>"));
+    assert!(!rust_feed.contains("draft-post"));
+    assert!(!rust_feed.contains("about.html"));
+    let web_feed = fs::read_to_string(output.join("tags/web/rss.xml"))?;
+    assert!(web_feed.contains("<link>http://test.com/about.html</link>"));
+    Ok(())
+}
+
+#[test]
+fn test_taxonomy_template_without_markers() -> anyhow::Result<()> {
+    let blog_template = "<html><body><!-- start_content -->\n<!-- end_content --></body></html>";
+    for (template, missing) in [
+        ("<ul>$GENERETO['title']</ul>", "Start marker"),
+        (
+            "<ul><!-- start_content -->$GENERETO['title']</ul>",
+            "End marker",
+        ),
+    ] {
+        let tmp_dir = TempDir::with_prefix("taxonomies")?;
+        let project_path = tmp_dir.path();
+        create_project(
+            project_path,
+            r#"
+template: default
+title: Test Blog
+url: http://test.com
+blog:
+  base_template: blog-index.html
+  destination: blog
+taxonomies:
+  - name: tags
+    index_template: tags-index.html
+"#,
+            blog_template,
+        )?;
+        fs::write(
+            project_path.join("templates/default/tags-index.html"),
+            template,
+        )?;
+
+        let error = genereto::run(project_path.into(), DraftsOptions::Build).unwrap_err();
+        let message = format!("{error:#}");
+        assert!(message.contains(missing), "{message}");
+        assert!(message.contains("tags-index.html"), "{message}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_taxonomy_pages_with_jinja() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("taxonomies")?;
+    let project_path = tmp_dir.path();
+    create_project(
+        project_path,
+        r#"
+template: default
+title: Test Blog
+url: http://test.com
+enable_jinja: true
+blog:
+  base_template: blog-index.html
+  index_name: index.html
+  destination: blog
+taxonomies:
+  - name: tags
+    template: tag.html
+    feed_path: feeds/{term}.xml
+"#,
+        "",
+    )?;
+    fs::write(
+        project_path.join("templates/default/tag.html"),
+        r#"{% if term %}<h1>{{ term.name }}</h1>{% for article in articles %}<a href="{{ article.file_name }}">{{ article.title }}</a>
+{% endfor %}<a href="{{ term.feed_url }}">RSS</a>{% else %}{% for term in taxonomy.terms %}<a href="{{ term.url }}">{{ term.name }} ({{ term.count }})</a>
+{% endfor %}{% endif %}"#,
+    )?;
+
+    genereto::run(project_path.into(), DraftsOptions::Build)?;
+    let output = project_path.join("output");
+
+    let rust = fs::read_to_string(output.join("tags/rust/index.html"))?;
+    assert!(rust.starts_with("<h1>Rust</h1>"));
+    assert!(rust.contains(r#"<a href="../../blog/third-post.html">third-post</a>"#));
+    assert!(!output.join("tags/rust/index-page-2.html").exists());
+    assert!(rust.contains(r#"<a href="http://test.com/feeds/rust.xml">RSS</a>"#));
+    assert!(output.join("feeds/rust.xml").exists());
+    assert!(!output.join("tags/rust/rss.xml").exists());
+
+    let tags = fs::read_to_string(output.join("tags/index.html"))?;
+    assert!(tags.contains(r#"<a href="http://test.com/tags/rust/index.html">Rust (3)</a>"#));
+    assert!(tags.contains(r#"<a href="http://test.com/tags/web/index.html">web (2)</a>"#));
+    Ok(())
+}
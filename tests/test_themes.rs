@@ -0,0 +1,189 @@
+use genereto::DraftsOptions;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+/// A theme shared by several sites, in its own folder.
+fn create_theme(theme_path: &Path) -> anyhow::Result<()> {
+    fs::create_dir_all(theme_path.join("res"))?;
+    fs::create_dir_all(theme_path.join("shortcodes"))?;
+    fs::write(
+        theme_path.join("index.html"),
+        "<html><body><!-- start_content -->\n<!-- end_content -->$GENERETO_INCLUDE['footer.html']</body></html>",
+    )?;
+    fs::write(
+        theme_path.join("blog.html"),
+        "<article>$GENERETO['title']<!-- start_content -->\n<!-- end_content --></article>$GENERETO_INCLUDE['footer.html']",
+    )?;
+    fs::write(
+        theme_path.join("blog-index.html"),
+        "<ul><!-- start_content --><li>$GENERETO['title']</li><!-- end_content --></ul>$GENERETO_INCLUDE['footer.html']",
+    )?;
+    fs::write(theme_path.join("footer.html"), "<footer>Theme</footer>")?;
+    fs::write(theme_path.join("res/style.css"), "body { color: black; }")?;
+    fs::write(theme_path.join("res/theme.js"), "console.log('theme');")?;
+    fs::write(
+        theme_path.join("shortcodes/note.html"),
+        "<aside>$GENERETO['body']</aside>",
+    )?;
+    Ok(())
+}
+
+/// A site using the theme, overriding only the footer and the stylesheet.
+fn create_project(project_path: &Path, theme_path: &Path) -> anyhow::Result<()> {
+    fs::create_dir_all(project_path.join("templates/site/res"))?;
+    fs::create_dir_all(project_path.join("content/blog"))?;
+    fs::write(
+        project_path.join("config.yml"),
+        format!(
+            r#"
+template: site
+themes: ["{}"]
+title: Test Blog
+url: http://test.com
+blog:
+  base_template: blog-index.html
+  destination: blog
+  generate_single_pages: true
+"#,
+            theme_path.display()
+        ),
+    )?;
+    fs::write(
+        project_path.join("templates/site/footer.html"),
+        "<footer>Site</footer>",
+    )?;
+    fs::write(
+        project_path.join("templates/site/res/style.css"),
+        "body { color: red; }",
+    )?;
+    fs::write(
+        project_path.join("content/about.md"),
+        "---\ntitle: About\n---\n\n{{< note >}}\nHello\n{{< /note >}}\n",
+    )?;
+    fs::write(
+        project_path.join("content/blog/first-post.md"),
+        "---\ntitle: First post\npublish_date: '2024-01-15'\n---\n\nContent.\n",
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_project_templates_override_the_theme() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("themes")?;
+    let theme_path = tmp_dir.path().join("theme");
+    let project_path = tmp_dir.path().join("site");
+    create_theme(&theme_path)?;
+    create_project(&project_path, &theme_path)?;
+
+    genereto::run(project_path.clone(), DraftsOptions::Hide)?;
+    let output = project_path.join("output");
+
+    let about = fs::read_to_string(output.join("about.html"))?;
+    assert!(about.contains("<footer>Site</footer>"));
+    assert!(about.contains("<aside>Hello</aside>"));
+    let post = fs::read_to_string(output.join("blog/first-post.html"))?;
+    assert!(post.contains("<article>First post"));
+    assert!(post.contains("<footer>Site</footer>"));
+    let blog_index = fs::read_to_string(output.join("blog/index.html"))?;
+    assert!(blog_index.contains("<li>First post</li>"));
+    assert!(blog_index.contains("<footer>Site</footer>"));
+
+    assert_eq!(
+        fs::read_to_string(output.join("res/style.css"))?,
+        "body { color: red; }"
+    );
+    assert_eq!(
+        fs::read_to_string(output.join("res/theme.js"))?,
+        "console.log('theme');"
+    );
+    assert!(!output.join("shortcodes").exists());
+    Ok(())
+}
+
+#[test]
+fn test_newly_overridden_asset_is_copied() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("themes")?;
+    let theme_path = tmp_dir.path().join("theme");
+    let project_path = tmp_dir.path().join("site");
+    create_theme(&theme_path)?;
+    create_project(&project_path, &theme_path)?;
+    genereto::run(project_path.clone(), DraftsOptions::Hide)?;
+
+    // Same size as the theme file, and older than its copy in the output.
+    let override_path = project_path.join("templates/site/res/theme.js");
+    fs::write(&override_path, "console.log('site!');")?;
+    fs::File::options()
+        .write(true)
+        .open(&override_path)?
+        .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(86400))?;
+    genereto::run(project_path.clone(), DraftsOptions::Hide)?;
+
+    assert_eq!(
+        fs::read_to_string(project_path.join("output/res/theme.js"))?,
+        "console.log('site!');"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_missing_template_mentions_the_template_folder() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("themes")?;
+    let theme_path = tmp_dir.path().join("theme");
+    let project_path = tmp_dir.path().join("site");
+    create_theme(&theme_path)?;
+    create_project(&project_path, &theme_path)?;
+    fs::remove_file(theme_path.join("index.html"))?;
+
+    let error = genereto::run(project_path.clone(), DraftsOptions::Hide).unwrap_err();
+    let message = format!("{error:#}");
+    assert!(message.contains("templates/site/index.html"), "{message}");
+    Ok(())
+}
+
+#[test]
+fn test_jinja_templates_from_the_theme() -> anyhow::Result<()> {
+    let tmp_dir = TempDir::with_prefix("themes")?;
+    let theme_path = tmp_dir.path().join("theme");
+    let project_path = tmp_dir.path().join("site");
+    fs::create_dir_all(theme_path.join("res"))?;
+    fs::create_dir_all(project_path.join("templates/site"))?;
+    fs::create_dir_all(project_path.join("content"))?;
+    fs::write(
+        project_path.join("config.yml"),
+        r#"
+template: site
+themes: ["../theme"]
+enable_jinja: true
+title: Test Site
+url: http://test.com
+"#,
+    )?;
+    fs::write(
+        theme_path.join("base.html"),
+        "<html><link href=\"{{ asset('res/style.css') }}\">{% block body %}{% endblock %}{% include 'footer.html' %}</html>",
+    )?;
+    fs::write(
+        theme_path.join("index.html"),
+        "{% extends 'base.html' %}{% block body %}<main>{{ content }}</main>{% endblock %}",
+    )?;
+    fs::write(theme_path.join("footer.html"), "<footer>Theme</footer>")?;
+    fs::write(theme_path.join("res/style.css"), "body { color: black; }")?;
+    fs::write(
+        project_path.join("templates/site/footer.html"),
+        "<footer>{{ site.title }}</footer>",
+    )?;
+    fs::write(
+        project_path.join("content/about.md"),
+        "---\ntitle: About\n---\n\nHello.\n",
+    )?;
+
+    genereto::run(project_path.clone(), DraftsOptions::Hide)?;
+
+    let about = fs::read_to_string(project_path.join("output/about.html"))?;
+    assert!(about.contains("<main><p>Hello.</p>"), "{about}");
+    assert!(about.contains("<footer>Test Site</footer>"));
+    assert!(about.contains("href=\"http://test.com/res/style.css?v="));
+    Ok(())
+}
@@ -42,7 +42,7 @@ blog:
         fs::write(temp_dir.join("content/blog").join(name), content).unwrap();
     }
 
-    GeneretoConfig::load_from_folder(temp_dir.to_path_buf()).unwrap()
+    GeneretoConfig::load_from_folder(temp_dir).unwrap()
 }
 
 #[test]
@@ -168,10 +168,16 @@ fn test_verify_date_matches() {
     let temp_dir = tempdir().unwrap();
     let config = create_test_project(
         temp_dir.path(),
-        &[(
-            "2024-01-01-test.md",
-            "---\ntitle: Test\npublish_date: '2024-01-01'\n---\n\nContent.\n",
-        )],
+        &[
+            (
+                "2024-01-01-test.md",
+                "---\ntitle: Test\npublish_date: '2024-01-01'\n---\n\nContent.\n",
+            ),
+            (
+                "2024-01-02-scheduled.md",
+                "---\ntitle: Test\npublish_date: '2024-01-02T09:30:00+01:00'\n---\n\nContent.\n",
+            ),
+        ],
     );
 
     let issues = verify::run_checks(&config, &[Check::DateMismatch], &config.output_dir_path);